@@ -0,0 +1,37 @@
+//! Prints the door network of every level as a graph.
+//!
+//! Usage: cargo run --bin doorgame-graph -- [--dot | --mermaid] [assets_dir]   (default: --dot ./assets)
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use bevy_game::level_graph::{GraphFormat, render_graph};
+use bevy_game::level_index::read_levels;
+
+fn main() -> ExitCode {
+    let mut format = GraphFormat::Dot;
+    let mut assets_dir = PathBuf::from("assets");
+    for arg in std::env::args().skip(1) {
+        match arg.strip_prefix("--") {
+            Some(name) => match GraphFormat::from_name(name) {
+                Some(chosen) => format = chosen,
+                None => {
+                    eprintln!("error: unknown option '{}' (expected --dot or --mermaid)", arg);
+                    return ExitCode::FAILURE;
+                }
+            },
+            None => assets_dir = PathBuf::from(arg),
+        }
+    }
+
+    let levels_dir = assets_dir.join("levels");
+    match read_levels(&levels_dir) {
+        Ok(levels) => {
+            print!("{}", render_graph(&levels, format));
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: can't read {}: {}", levels_dir.display(), err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Checks level files for mistakes that would otherwise only show up in-game.
+//!
+//! Usage: cargo run --bin doorgame-lint [assets_dir]   (default: ./assets)
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use bevy_game::level_lint::{Severity, lint_assets};
+
+fn main() -> ExitCode {
+    let assets_dir = std::env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("assets"));
+
+    let issues = match lint_assets(&assets_dir) {
+        Ok(issues) => issues,
+        Err(err) => {
+            eprintln!("error: can't read {}: {}", assets_dir.join("levels").display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+    println!("{} error(s), {} warning(s)", errors, warnings);
+
+    if errors > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::art::SCARY_DOOR_ART;
+
+/// A door boss, loaded from `assets/bosses/<id>.boss.ron` and referenced by `LevelData.boss`
+#[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
+pub struct BossData {
+    pub name: String,
+    pub art: String,
+    #[serde(default = "default_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    #[serde(default)]
+    pub arena: ArenaData,
+    /// How far above the top of the arena the boss art sits
+    #[serde(default = "default_art_height")]
+    pub art_height: f32,
+    /// Indexed by the `boss_phase` story flag (phase 1 = first entry)
+    pub phases: Vec<BossPhase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaData {
+    pub size: (f32, f32),
+    pub position: (f32, f32),
+}
+
+impl Default for ArenaData {
+    fn default() -> Self {
+        Self {
+            size: (300.0, 200.0),
+            position: (0.0, -100.0), // Lower part of screen
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BossPhase {
+    /// Seconds between pattern steps (a step's `wait` overrides it)
+    #[serde(default = "default_interval")]
+    pub interval: f32,
+    /// Pattern steps fired before ProjectilesDone (RestartProjectiles can override)
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// Fired in order, looping back to the start
+    #[serde(default = "default_patterns")]
+    pub patterns: Vec<PatternStep>,
+}
+
+/// One entry in a phase's attack sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternStep {
+    pub pattern: Pattern,
+    #[serde(default = "default_glyph")]
+    pub glyph: String,
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Random +/- added to each projectile's speed
+    #[serde(default)]
+    pub speed_variance: f32,
+    /// Projectiles in this step (ignored by Wall, which fills the arena width)
+    #[serde(default = "default_shots")]
+    pub count: u32,
+    /// Seconds before the next step. `Some(0.0)` fires the next step in the same frame.
+    #[serde(default)]
+    pub wait: Option<f32>,
+    /// Seconds each projectile lives (defaults to `DEFAULT_LIFETIME`)
+    #[serde(default)]
+    pub lifetime: Option<f32>,
+    /// Projectiles vanish when they touch a wall instead of passing through
+    #[serde(default)]
+    pub despawn_on_wall: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Pattern {
+    /// Fan of `count` shots at the player, `spread` degrees wide, from a random x within `offset` of the boss
+    Aimed {
+        #[serde(default)]
+        spread: f32,
+        #[serde(default)]
+        offset: f32,
+    },
+    /// `count` shots evenly around the boss
+    Radial,
+    /// Radial burst that turns `turn` degrees every time it fires
+    Spiral { turn: f32 },
+    /// Row of shots across the top of the arena moving down, with a random gap `gap` wide
+    Wall { gap: f32 },
+    /// Shots at the player that weave side to side
+    Sine { amplitude: f32, frequency: f32 },
+    /// `count` shots falling from random points along the top of the arena
+    Rain,
+    /// Shots that steer toward the player, turning at most `turn_rate` degrees/second
+    Homing { turn_rate: f32 },
+}
+
+impl Default for BossPhase {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            count: default_count(),
+            patterns: default_patterns(),
+        }
+    }
+}
+
+// Used when a level has no boss file (or it failed to load)
+impl Default for BossData {
+    fn default() -> Self {
+        Self {
+            name: "SCARY DOOR".to_string(),
+            art: SCARY_DOOR_ART.to_string(),
+            color: default_color(),
+            font_size: default_font_size(),
+            arena: ArenaData::default(),
+            art_height: default_art_height(),
+            phases: vec![BossPhase::default()],
+        }
+    }
+}
+
+impl BossData {
+    /// Phase for a `boss_phase` flag value, holding on the last phase past the end
+    pub fn phase(&self, number: i32) -> BossPhase {
+        let index = (number.max(1) - 1) as usize;
+        self.phases
+            .get(index)
+            .or(self.phases.last())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+fn default_color() -> (f32, f32, f32) {
+    (0.8, 0.2, 0.2)
+}
+
+fn default_font_size() -> f32 {
+    16.0
+}
+
+fn default_art_height() -> f32 {
+    200.0
+}
+
+fn default_interval() -> f32 {
+    1.0
+}
+
+fn default_count() -> u32 {
+    15
+}
+
+// The original attack: one aimed shot from near the boss at a random speed (150-300)
+fn default_patterns() -> Vec<PatternStep> {
+    vec![PatternStep {
+        pattern: Pattern::Aimed { spread: 0.0, offset: 100.0 },
+        glyph: default_glyph(),
+        speed: 225.0,
+        speed_variance: 75.0,
+        count: 1,
+        wait: None,
+        lifetime: None,
+        despawn_on_wall: false,
+    }]
+}
+
+fn default_glyph() -> String {
+    "{=}".to_string()
+}
+
+fn default_speed() -> f32 {
+    200.0
+}
+
+fn default_shots() -> u32 {
+    1
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::boss_schema::{Pattern, PatternStep};
+use crate::projectile::{spawn_projectile_at, Homing, Projectile, ProjectilePool, Wave, DEFAULT_LIFETIME};
+
+const PROJECTILE_Z: f32 = 5.0;
+/// Distance between shots in a Wall (about one projectile wide)
+const WALL_SPACING: f32 = 24.0;
+
+/// Where a pattern is fired from and at
+pub struct PatternTarget {
+    pub boss: Vec2,
+    pub player: Vec2,
+    /// Arena centre and size, used by patterns that sweep the arena (Wall, Rain)
+    pub arena_center: Vec2,
+    pub arena_size: Vec2,
+}
+
+/// Spawns one step's projectiles. `spiral_angle` carries Spiral rotation between steps.
+pub fn fire_pattern(
+    commands: &mut Commands,
+    pool: &mut ProjectilePool,
+    step: &PatternStep,
+    target: &PatternTarget,
+    spiral_angle: &mut f32,
+) {
+    let mut rng = rand::rng();
+    let count = step.count.max(1);
+    let mut speed = || {
+        if step.speed_variance > 0.0 {
+            step.speed + rng.random_range(-step.speed_variance..=step.speed_variance)
+        } else {
+            step.speed
+        }
+    };
+    let arena_top = target.arena_center.y + target.arena_size.y / 2.0;
+    let arena_left = target.arena_center.x - target.arena_size.x / 2.0;
+
+    match &step.pattern {
+        Pattern::Aimed { spread, offset } => {
+            let offset_x = if *offset > 0.0 { rand::rng().random_range(-offset..=*offset) } else { 0.0 };
+            let origin = target.boss + Vec2::new(offset_x, 0.0);
+            let aim = (target.player - origin).normalize_or(Vec2::NEG_Y);
+            for angle in fan_angles(count, spread.to_radians()) {
+                let velocity = Vec2::from_angle(angle).rotate(aim) * speed();
+                spawn(commands, pool, step, origin, velocity);
+            }
+        }
+        Pattern::Radial => {
+            for i in 0..count {
+                let direction = Vec2::from_angle(i as f32 * TAU / count as f32);
+                spawn(commands, pool, step, target.boss, direction * speed());
+            }
+        }
+        Pattern::Spiral { turn } => {
+            for i in 0..count {
+                let direction = Vec2::from_angle(*spiral_angle + i as f32 * TAU / count as f32);
+                spawn(commands, pool, step, target.boss, direction * speed());
+            }
+            *spiral_angle = (*spiral_angle + turn.to_radians()) % TAU;
+        }
+        Pattern::Wall { gap } => {
+            let Some(width) = arena_width(target) else { return };
+            let half_gap = (gap / 2.0).min(width / 2.0);
+            let gap_center = if half_gap * 2.0 < width {
+                rand::rng().random_range(arena_left + half_gap..=arena_left + width - half_gap)
+            } else {
+                target.arena_center.x
+            };
+            let columns = (width / WALL_SPACING).floor() as u32 + 1;
+            for i in 0..columns {
+                let x = arena_left + i as f32 * WALL_SPACING;
+                if (x - gap_center).abs() < half_gap {
+                    continue;
+                }
+                spawn(commands, pool, step, Vec2::new(x, arena_top), Vec2::new(0.0, -speed()));
+            }
+        }
+        Pattern::Sine { amplitude, frequency } => {
+            let aim = (target.player - target.boss).normalize_or(Vec2::NEG_Y);
+            for i in 0..count {
+                // Offset each shot along the wave so a stream snakes instead of stacking
+                let age = i as f32 / (count as f32 * frequency.max(0.01));
+                let entity = spawn(commands, pool, step, target.boss, aim * speed());
+                commands.entity(entity).insert(Wave { amplitude: *amplitude, frequency: *frequency, age });
+            }
+        }
+        Pattern::Rain => {
+            let Some(width) = arena_width(target) else { return };
+            for _ in 0..count {
+                let x = arena_left + rand::rng().random_range(0.0..=width);
+                spawn(commands, pool, step, Vec2::new(x, arena_top), Vec2::new(0.0, -speed()));
+            }
+        }
+        Pattern::Homing { turn_rate } => {
+            let aim = (target.player - target.boss).normalize_or(Vec2::NEG_Y);
+            for angle in fan_angles(count, 30f32.to_radians()) {
+                let velocity = Vec2::from_angle(angle).rotate(aim) * speed();
+                let entity = spawn(commands, pool, step, target.boss, velocity);
+                commands.entity(entity).insert(Homing { turn_rate: turn_rate.to_radians() });
+            }
+        }
+    }
+}
+
+/// Arena width for Wall and Rain: a negative width from a boss file counts as 0, and a
+/// NaN or infinite one fires nothing (doorgame-lint reports both)
+fn arena_width(target: &PatternTarget) -> Option<f32> {
+    let width = target.arena_size.x;
+    width.is_finite().then(|| width.max(0.0))
+}
+
+/// Angles (radians) for `count` shots spread evenly across a fan `width` wide, centred on 0
+fn fan_angles(count: u32, width: f32) -> impl Iterator<Item = f32> {
+    (0..count).map(move |i| {
+        if count == 1 {
+            0.0
+        } else {
+            -width / 2.0 + width * i as f32 / (count - 1) as f32
+        }
+    })
+}
+
+fn spawn(commands: &mut Commands, pool: &mut ProjectilePool, step: &PatternStep, pos: Vec2, velocity: Vec2) -> Entity {
+    let projectile = Projectile {
+        lifetime: step.lifetime.unwrap_or(DEFAULT_LIFETIME),
+        despawn_on_wall: step.despawn_on_wall,
+        ..Projectile::new(velocity)
+    };
+    spawn_projectile_at(commands, pool, pos.extend(PROJECTILE_Z), projectile, &step.glyph)
+}
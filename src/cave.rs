@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use mapgen::MapBuilder;
+use mapgen::filter::{
+    BspInterior, BspRooms, CellularAutomata, DrunkardsWalk, MazeBuilder, NearestCorridors, NoiseGenerator, SimpleRooms,
+    VoronoiHive,
+};
+use rand_mapgen::SeedableRng;
+use rand_mapgen::rngs::StdRng;
+use crate::level::{TILE_SIZE, spawn_floor_at, spawn_wall_at};
+use crate::level_schema::{CaveGenerator, CaveSettings, LevelData};
+
+/// Smallest width or height a cave is generated at
+pub const MIN_CAVE_SIZE: usize = 10;
+
+/// Walkable tiles of a generated `cave` room. Every walkable tile can be reached from every other.
+pub struct CaveLayout {
+    pub width: usize,
+    pub height: usize,
+    walkable: Vec<bool>,
+}
+
+impl CaveLayout {
+    /// Same settings and seed, same layout
+    pub fn generate(settings: &CaveSettings, seed: u64) -> Self {
+        // mapgen's generators need some room to work with
+        let (width, height) = (settings.size.0.max(MIN_CAVE_SIZE), settings.size.1.max(MIN_CAVE_SIZE));
+        let mut builder = MapBuilder::new(width, height);
+        match settings.generator {
+            CaveGenerator::CellularAutomata => builder.with(NoiseGenerator::uniform()).with(CellularAutomata::new()),
+            CaveGenerator::BspRooms => builder.with(BspRooms::new()).with(NearestCorridors::new()),
+            CaveGenerator::BspInterior => builder.with(BspInterior::new()),
+            CaveGenerator::SimpleRooms => builder.with(SimpleRooms::new()).with(NearestCorridors::new()),
+            CaveGenerator::DrunkardsWalk => builder.with(DrunkardsWalk::winding_passages()),
+            CaveGenerator::Maze => builder.with(MazeBuilder::new()),
+            CaveGenerator::Voronoi => builder.with(VoronoiHive::new()),
+        };
+        let map = builder.build_with_rng(&mut StdRng::seed_from_u64(seed));
+
+        let mut layout = Self { width, height, walkable: map.walkables };
+        // Nothing is spawned past the edge, so the edge has to be solid
+        for x in 0..width {
+            layout.set_solid(x, 0);
+            layout.set_solid(x, height - 1);
+        }
+        for y in 0..height {
+            layout.set_solid(0, y);
+            layout.set_solid(width - 1, y);
+        }
+        layout.keep_largest_region();
+        layout
+    }
+
+    pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.walkable[y * self.width + x]
+    }
+
+    pub fn walkable_count(&self) -> usize {
+        self.walkable.iter().filter(|walkable| **walkable).count()
+    }
+
+    /// World position of a tile
+    pub fn position(&self, x: usize, y: usize) -> (f32, f32) {
+        (
+            x as f32 * TILE_SIZE - (self.width as f32 * TILE_SIZE) / 2.0,
+            y as f32 * TILE_SIZE - (self.height as f32 * TILE_SIZE) / 2.0,
+        )
+    }
+
+    /// Walkable tile closest to a world position
+    pub fn nearest_walkable(&self, position: (f32, f32)) -> Option<(usize, usize)> {
+        let target = Vec2::new(position.0, position.1);
+        let mut best: Option<((usize, usize), f32)> = None;
+        for index in (0..self.walkable.len()).filter(|index| self.walkable[*index]) {
+            let tile = (index % self.width, index / self.width);
+            let (x, y) = self.position(tile.0, tile.1);
+            let distance = Vec2::new(x, y).distance_squared(target);
+            if best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                best = Some((tile, distance));
+            }
+        }
+        best.map(|(tile, _)| tile)
+    }
+
+    /// Steps from `start` to every tile, walking between neighbouring walkable tiles (None if unreachable)
+    pub fn distances_from(&self, start: (usize, usize)) -> Vec<Option<u32>> {
+        let mut distances = vec![None; self.walkable.len()];
+        if !self.is_walkable(start.0, start.1) {
+            return distances;
+        }
+        let mut queue = VecDeque::from([start]);
+        distances[start.1 * self.width + start.0] = Some(0);
+        while let Some((x, y)) = queue.pop_front() {
+            let distance = distances[y * self.width + x].unwrap_or(0);
+            for (nx, ny) in self.neighbours(x, y) {
+                let index = ny * self.width + nx;
+                if self.walkable[index] && distances[index].is_none() {
+                    distances[index] = Some(distance + 1);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        distances
+    }
+
+    fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        [(0, 1), (2, 1), (1, 0), (1, 2)].into_iter().filter_map(move |(dx, dy)| {
+            let nx = (x + dx).checked_sub(1)?;
+            let ny = (y + dy).checked_sub(1)?;
+            (nx < width && ny < height).then_some((nx, ny))
+        })
+    }
+
+    fn set_solid(&mut self, x: usize, y: usize) {
+        self.walkable[y * self.width + x] = false;
+    }
+
+    // Flood fill each pocket of floor and wall off all but the biggest, so nothing spawns
+    // somewhere the player can't get to
+    fn keep_largest_region(&mut self) {
+        let mut region_of: Vec<Option<usize>> = vec![None; self.walkable.len()];
+        let mut sizes = Vec::new();
+        for index in 0..self.walkable.len() {
+            if !self.walkable[index] || region_of[index].is_some() {
+                continue;
+            }
+            let region = sizes.len();
+            let distances = self.distances_from((index % self.width, index / self.width));
+            let mut size = 0;
+            for (tile, distance) in distances.iter().enumerate() {
+                if distance.is_some() {
+                    region_of[tile] = Some(region);
+                    size += 1;
+                }
+            }
+            sizes.push(size);
+        }
+
+        // First of the biggest, so ties always pick the same region
+        let Some(largest) = (0..sizes.len()).max_by_key(|region| (sizes[*region], std::cmp::Reverse(*region))) else {
+            return;
+        };
+        for (walkable, region) in self.walkable.iter_mut().zip(region_of) {
+            *walkable = region == Some(largest);
+        }
+    }
+}
+
+/// Give a `cave` room with no fixed seed a fresh one. Done once as the room loads, so the
+/// layout in `LoadedLevelData` is the one on screen.
+pub fn roll_cave_seed(level: &mut LevelData) {
+    if level.room_type != "cave" {
+        return;
+    }
+    let cave = level.cave.get_or_insert_with(CaveSettings::default);
+    if cave.seed.is_none() {
+        cave.seed = Some(rand::random());
+    }
+}
+
+/// Generate a `cave` room's layout and move things onto it: the player to the walkable tile
+/// nearest `player_start`, doors as far from the player and each other as the cave allows,
+/// and NPCs and items to the nearest walkable tile. None for other room types.
+pub fn place_in_cave(level: &mut LevelData) -> Option<CaveLayout> {
+    if level.room_type != "cave" {
+        return None;
+    }
+    let settings = level.cave.clone().unwrap_or_default();
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    let cave = CaveLayout::generate(&settings, seed);
+    info!("Generated {:?} cave with seed {} ({} floor tiles)", settings.generator, seed, cave.walkable_count());
+
+    let Some(start) = cave.nearest_walkable(level.player_start) else {
+        warn!("Cave with seed {} has no floor", seed);
+        return Some(cave);
+    };
+    level.player_start = cave.position(start.0, start.1);
+
+    // Greedy farthest point: each door takes the tile furthest (in steps) from the player and
+    // the doors already placed
+    let mut nearest_taken = cave.distances_from(start);
+    for door in &mut level.doors {
+        let Some(best) = (0..nearest_taken.len()).max_by_key(|index| (nearest_taken[*index], std::cmp::Reverse(*index))) else {
+            break;
+        };
+        let tile = (best % cave.width, best / cave.width);
+        door.position = cave.position(tile.0, tile.1);
+        for (taken, distance) in nearest_taken.iter_mut().zip(cave.distances_from(tile)) {
+            *taken = (*taken).min(distance);
+        }
+    }
+
+    let snap = |position: &mut (f32, f32)| {
+        if let Some((x, y)) = cave.nearest_walkable(*position) {
+            *position = cave.position(x, y);
+        }
+    };
+    for npc in &mut level.npcs {
+        snap(&mut npc.position);
+    }
+    for item in &mut level.items {
+        snap(&mut item.position);
+    }
+    Some(cave)
+}
+
+pub fn spawn_cave_tiles(commands: &mut Commands, cave: &CaveLayout) {
+    for y in 0..cave.height {
+        for x in 0..cave.width {
+            let (px, py) = cave.position(x, y);
+            let pos = Vec3::new(px, py, 0.0);
+            if cave.is_walkable(x, y) {
+                spawn_floor_at(commands, pos);
+            } else {
+                spawn_wall_at(commands, pos, TILE_SIZE);
+            }
+        }
+    }
+}
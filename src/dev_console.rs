@@ -0,0 +1,276 @@
+use bevy::input::InputSystems;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use crate::level::CurrentLevel;
+use crate::level_event::LevelEvent;
+use crate::player::{GodMode, PlayerHealth};
+use crate::run::{CurrentRun, DefaultRun, start_run};
+use crate::run_schema::RunData;
+use crate::state::GameState;
+use crate::story_flags::{FlagValue, StoryFlags};
+
+/// Lines of output kept on screen
+const VISIBLE_LINES: usize = 14;
+
+const HELP: &str = "\
+flag set <key> <value>   value in RON: Bool(true), Text(\"traded\"), Number(3)
+flag get <key>
+flag list
+goto <level_id>          load assets/levels/<level_id>.ron
+run [seed]               start a run (random seed if none), or print the current one
+state <name>             Playing, Dialogue, BossFight, LoadingNewLevel
+heal                     restore full health
+god                      toggle taking no damage
+event <name> [value]     e.g. BossDefeated, LevelEntered, DoorOpened Left Door, NpcDied duck, ZoneEntered <zone>
+clear";
+
+/// In-game console for jumping to a scenario (` to open, `--features dev` only)
+pub struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DevConsole>()
+           // Typing into the console must not also move the player or pause the game
+           .add_systems(PreUpdate, read_console_keys.after(InputSystems))
+           .add_systems(Update, (run_console_commands, update_console_panel).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DevConsole {
+    pub open: bool,
+    input: String,
+    log: Vec<String>,
+    /// Entered lines waiting to run
+    pending: Vec<String>,
+}
+
+impl DevConsole {
+    /// Run a command next frame, as if typed and entered
+    pub fn submit(&mut self, line: &str) {
+        self.pending.push(line.to_string());
+    }
+
+    /// Everything the console has printed, oldest first
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+}
+
+#[derive(Component)]
+struct DevConsolePanel;
+
+#[derive(Component)]
+struct DevConsoleText;
+
+fn read_console_keys(
+    mut key_messages: MessageReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut console: ResMut<DevConsole>,
+) {
+    let was_open = console.open;
+    for message in key_messages.read() {
+        if message.state != ButtonState::Pressed {
+            continue;
+        }
+        if message.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match message.key_code {
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.submit(&line);
+                }
+            }
+            KeyCode::Backspace => {
+                console.input.pop();
+            }
+            KeyCode::Escape => console.open = false,
+            _ => {
+                if let Some(text) = &message.text {
+                    console.input.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+
+    // Also on the frame it closes, or the Escape that closed it would pause the game
+    if was_open || console.open {
+        keys.reset_all();
+    }
+}
+
+fn run_console_commands(
+    mut console: ResMut<DevConsole>,
+    mut story_flags: ResMut<StoryFlags>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut health: ResMut<PlayerHealth>,
+    mut god_mode: ResMut<GodMode>,
+    mut level_events: MessageWriter<LevelEvent>,
+    mut current_run: ResMut<CurrentRun>,
+    default_run: Res<DefaultRun>,
+    run_assets: Res<Assets<RunData>>,
+) {
+    if console.pending.is_empty() {
+        return;
+    }
+    for line in std::mem::take(&mut console.pending) {
+        console.log.push(format!("> {}", line));
+        let (command, args) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let args = args.trim();
+
+        let reply = match command {
+            "help" => Ok(HELP.to_string()),
+            "clear" => {
+                console.log.clear();
+                continue;
+            }
+            "flag" => run_flag_command(args, &mut story_flags),
+            "goto" if !args.is_empty() => {
+                current_level.level_id = args.to_string();
+                current_level.loaded = false;
+                next_state.set(GameState::LoadingNewLevel);
+                Ok(format!("loading {}", args))
+            }
+            "run" => match (args.parse::<u64>(), &current_run.0) {
+                (Err(_), Some(run)) if args.is_empty() => Ok(format!("seed {}, {} rooms deep", run.seed, run.plan.depth())),
+                (Err(_), _) if !args.is_empty() => Err(format!("bad seed '{}'", args)),
+                (seed, _) => match run_assets.get(&default_run.0) {
+                    Some(run_data) => {
+                        let seed = seed.unwrap_or_else(|_| rand::random());
+                        start_run(&mut current_run, &mut current_level, run_data, seed);
+                        next_state.set(GameState::LoadingNewLevel);
+                        Ok(format!("starting run with seed {}", seed))
+                    }
+                    None => Err("run file isn't loaded".to_string()),
+                },
+            },
+            "state" => match GameState::from_name(args) {
+                Some(state) => {
+                    next_state.set(state);
+                    Ok(format!("state -> {:?}", state))
+                }
+                None => Err(format!("unknown state '{}'", args)),
+            },
+            "heal" => {
+                health.current = health.max;
+                Ok(format!("health {}/{}", health.current, health.max))
+            }
+            "god" => {
+                god_mode.0 = !god_mode.0;
+                Ok(format!("god mode {}", if god_mode.0 { "on" } else { "off" }))
+            }
+            "event" => {
+                let (name, value) = match args.split_once(' ') {
+                    Some((name, value)) => (name, Some(value.trim())),
+                    None => (args, None),
+                };
+                match LevelEvent::from_name(name, value) {
+                    Some(event) => {
+                        let reply = format!("sent {:?}", event);
+                        level_events.write(event);
+                        Ok(reply)
+                    }
+                    None => Err(format!("unknown event '{}' (DoorOpened, ItemPicked, NpcDied and the zone events need a value)", args)),
+                }
+            }
+            _ => Err(format!("unknown command '{}' (try help)", line.trim())),
+        };
+
+        match reply {
+            Ok(text) => {
+                info!("Console: {} -> {}", line, text);
+                console.log.extend(text.lines().map(str::to_string));
+            }
+            Err(text) => {
+                warn!("Console: {} -> {}", line, text);
+                console.log.push(format!("error: {}", text));
+            }
+        }
+    }
+}
+
+fn run_flag_command(args: &str, story_flags: &mut StoryFlags) -> Result<String, String> {
+    let (sub, rest) = args.split_once(' ').unwrap_or((args, ""));
+    match sub {
+        "set" => {
+            let (key, value) = rest.trim().split_once(' ').ok_or_else(|| "usage: flag set <key> <value>".to_string())?;
+            let value: FlagValue = ron::from_str(value.trim()).map_err(|err| format!("bad value: {}", err))?;
+            let reply = format!("{} = {:?}", key, value);
+            story_flags.set(key, value);
+            Ok(reply)
+        }
+        "get" => match story_flags.get(rest.trim()) {
+            Some(value) => Ok(format!("{} = {:?}", rest.trim(), value)),
+            None => Err(format!("flag '{}' is not set", rest.trim())),
+        },
+        "list" => {
+            let mut flags: Vec<String> = story_flags.iter().map(|(key, value)| format!("{} = {:?}", key, value)).collect();
+            flags.sort();
+            Ok(if flags.is_empty() { "no flags set".to_string() } else { flags.join("\n") })
+        }
+        _ => Err("usage: flag set|get|list".to_string()),
+    }
+}
+
+fn panel_text(console: &DevConsole) -> String {
+    let start = console.log.len().saturating_sub(VISIBLE_LINES);
+    let mut text = console.log[start..].join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(&format!("> {}_", console.input));
+    text
+}
+
+fn update_console_panel(
+    mut commands: Commands,
+    console: Res<DevConsole>,
+    panels: Query<Entity, With<DevConsolePanel>>,
+    mut texts: Query<&mut Text, With<DevConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    if !console.open {
+        for panel in &panels {
+            commands.entity(panel).despawn();
+        }
+        return;
+    }
+    if panels.is_empty() {
+        commands.spawn((
+            DevConsolePanel,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(100),
+        )).with_children(|parent| {
+            parent.spawn((
+                DevConsoleText,
+                Text::new(panel_text(&console)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 1.0, 0.6)),
+            ));
+        });
+        return;
+    }
+    for mut text in &mut texts {
+        **text = panel_text(&console);
+    }
+}
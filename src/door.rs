@@ -2,28 +2,178 @@ use bevy::prelude::*;
 use crate::audio::play_sfx;
 use crate::state::GameState;
 use crate::hitbox::PlayerTouchedSomething;
+use crate::inventory::Inventory;
 use crate::level::{LevelDoor, CurrentLevel};
+use crate::level_entity::LevelEntity;
+use crate::level_event::LevelEvent;
+use crate::level_schema::DoorHint;
+use crate::player::Player;
+use crate::story_flags::StoryFlags;
 
+const DOOR_MESSAGE_SECONDS: f32 = 1.5;
+/// How close the player has to be for a door's label and hint to show
+pub const HINT_RANGE: f32 = 200.0;
+
+/// Floating text shown above a door that refused to open
+#[derive(Component)]
+pub struct DoorMessage {
+    pub door: Entity,
+    pub timer: Timer,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_door_touch(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut messages: MessageReader<PlayerTouchedSomething>,
-    doors: Query<&LevelDoor>,
+    doors: Query<(&LevelDoor, &Transform)>,
+    door_messages: Query<&DoorMessage>,
+    mut inventory: ResMut<Inventory>,
+    story_flags: Res<StoryFlags>,
     mut current_level: ResMut<CurrentLevel>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut level_events: MessageWriter<LevelEvent>,
 ) {
     for message in messages.read() {
         // Try to get the LevelDoor component from the touched entity
-        if let Ok(door) = doors.get(message.messaging_entity) {
-            info!("Door hit! Loading level: {}", door.leads_to);
-            //play the door opening sfx
-            play_sfx(&mut commands, &asset_server, "creaking_door", "mp3");
-            // Update which level to load next
-            current_level.level_id = door.leads_to.clone();
-            current_level.loaded = false;
-
-            next_state.set(GameState::LoadingNewLevel);
+        let Ok((door, door_transform)) = doors.get(message.messaging_entity) else { continue };
+
+        if door.locked {
+            let key = door.required_key();
+            if !inventory.has_key(key) {
+                // Player touches the door every frame, only complain while no message is showing
+                if door_messages.iter().any(|m| m.door == message.messaging_entity) {
+                    continue;
+                }
+                info!("Door '{}' is locked (needs '{}')", door.label, key);
+                play_sfx(&mut commands, &asset_server, "door_locked", "wav");
+                spawn_door_message(&mut commands, message.messaging_entity, door_transform, "Locked.");
+                continue;
+            }
+
+            if door.consume_key {
+                inventory.take_key(key);
+                info!("Used key '{}' on door '{}'", key, door.label);
+            } else {
+                info!("Key '{}' opened door '{}'", key, door.label);
+            }
         }
+
+        // Destination can depend on story flags (leads_to_if)
+        let destination = door.destination(&story_flags);
+        info!("Door hit! Loading level: {}", destination);
+        //play the door opening sfx
+        play_sfx(&mut commands, &asset_server, "creaking_door", "mp3");
+        // Update which level to load next
+        current_level.level_id = destination.to_string();
+        current_level.loaded = false;
+        level_events.write(LevelEvent::DoorOpened { label: door.label.clone() });
+
+        next_state.set(GameState::LoadingNewLevel);
+        break; // Only walk through one door
+    }
+}
+
+fn spawn_door_message(commands: &mut Commands, door: Entity, door_transform: &Transform, text: &str) {
+    let pos = door_transform.translation + Vec3::new(0.0, 80.0, 1.0);
+    commands.spawn((
+        Text2d::new(text),
+        TextFont { font_size: 20.0, ..default() },
+        TextColor(Color::srgb(0.9, 0.7, 0.2)),
+        Transform::from_translation(pos),
+        DoorMessage { door, timer: Timer::from_seconds(DOOR_MESSAGE_SECONDS, TimerMode::Once) },
+        LevelEntity,
+    ));
+}
+
+/// Label and hint floating under a door the player is near
+#[derive(Component)]
+pub struct DoorHintText {
+    pub door: Entity,
+}
+
+/// Lines shown under a door: icon, label (if `show_label`), then the hint's text
+pub fn door_hint_text(door: &LevelDoor, hint: Option<&DoorHint>) -> String {
+    let mut lines = Vec::new();
+    if let Some(icon) = hint.and_then(|hint| hint.icon.as_ref()) {
+        lines.push(icon.clone());
+    }
+    if door.show_label && !door.label.is_empty() {
+        lines.push(door.label.clone());
+    }
+    if let Some(text) = hint.and_then(|hint| hint.text.as_ref()) {
+        lines.push(text.clone());
+    }
+    lines.join("\n")
+}
+
+// Show the label and hint of doors the player is near, and tint them. Checked every frame so a
+// lantern picked up mid-room (or a roaming door) updates straight away.
+#[allow(clippy::type_complexity)]
+fn show_door_hints(
+    mut commands: Commands,
+    story_flags: Res<StoryFlags>,
+    player: Single<&Transform, With<Player>>,
+    mut doors: Query<(Entity, &LevelDoor, &Transform, &mut TextColor), Without<DoorHintText>>,
+    mut hint_texts: Query<(Entity, &DoorHintText, &mut Text2d, &mut Transform), (Without<LevelDoor>, Without<Player>)>,
+) {
+    // Doors can go mid-room (DespawnTagged), taking their hint with them
+    for (entity, hint_text, _, _) in &hint_texts {
+        if !doors.contains(hint_text.door) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let player_pos = player.translation.truncate();
+    for (door_entity, door, door_transform, mut door_color) in &mut doors {
+        let near = door_transform.translation.truncate().distance(player_pos) < HINT_RANGE;
+        let hint = if near { door.hint(&story_flags) } else { None };
+
+        let color = hint.and_then(|hint| hint.tint).map_or(Color::WHITE, |(r, g, b)| Color::srgb(r, g, b));
+        if door_color.0 != color {
+            door_color.0 = color;
+        }
+
+        let text = if near { door_hint_text(door, hint) } else { String::new() };
+        let pos = door_transform.translation + Vec3::new(0.0, -95.0, 1.0);
+        let existing = hint_texts.iter_mut().find(|(_, hint_text, _, _)| hint_text.door == door_entity);
+        match (existing, text.is_empty()) {
+            (Some((entity, _, _, _)), true) => commands.entity(entity).despawn(),
+            (Some((_, _, mut shown, mut transform)), false) => {
+                if shown.0 != text {
+                    shown.0 = text;
+                }
+                transform.translation = pos;
+            }
+            (None, false) => {
+                commands.spawn((
+                    Text2d::new(text),
+                    TextFont { font_size: 16.0, ..default() },
+                    TextColor(Color::srgb(0.7, 0.75, 0.9)),
+                    TextLayout::new_with_justify(Justify::Center),
+                    Transform::from_translation(pos),
+                    DoorHintText { door: door_entity },
+                    LevelEntity,
+                ));
+            }
+            (None, true) => {}
+        }
+    }
+}
+
+// Fade out and remove door messages
+fn fade_door_messages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut DoorMessage, &mut TextColor)>,
+) {
+    for (entity, mut message, mut color) in &mut query {
+        message.timer.tick(time.delta());
+        if message.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        color.0.set_alpha(1.0 - message.timer.fraction());
     }
 }
 
@@ -33,6 +183,10 @@ impl Plugin for DoorPlugin {
     fn build(&self, app: &mut App) {
         // Doors are spawned by level.rs from RON data
         // This plugin just handles the touch interaction
-        app.add_systems(Update, handle_door_touch.run_if(in_state(GameState::Playing)));
+        app.add_systems(Update, (
+            handle_door_touch,
+            fade_door_messages,
+            show_door_hints,
+        ).run_if(in_state(GameState::Playing)));
     }
-}
\ No newline at end of file
+}
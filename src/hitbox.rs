@@ -14,7 +14,7 @@ pub struct PlayerTouchedSomething {
     pub messaging_entity: Entity,
 }
 
-fn detect_col_with_player(
+pub fn detect_col_with_player(
     player_query: Single<(&Transform, &HitBox), With<Player>>,
     other_query: Query<(Entity, &Transform, &HitBox), Without<Player>>,
     mut messages: MessageWriter<PlayerTouchedSomething>,
@@ -31,7 +31,7 @@ fn detect_col_with_player(
 
         if overlap_x && overlap_y {
             messages.write(PlayerTouchedSomething { messaging_entity: entity });
-            info!("overlapping!");
+            trace!("overlapping!");
         }
     }
 }
@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Name of the key that opens any locked door without a specific `key_required`
+pub const GENERIC_KEY: &str = "key";
+
+/// Keys the player is carrying between rooms.
+/// Keys are named so a door can ask for a specific one ("rusty_key"),
+/// and counted so the same key can be held more than once.
+#[derive(Resource, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    keys: HashMap<String, u32>,
+}
+
+impl Inventory {
+    pub fn add_key(&mut self, name: &str) {
+        *self.keys.entry(name.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn has_key(&self, name: &str) -> bool {
+        self.key_count(name) > 0
+    }
+
+    pub fn key_count(&self, name: &str) -> u32 {
+        self.keys.get(name).copied().unwrap_or(0)
+    }
+
+    /// Remove one copy of a key. Returns false if the player didn't have it.
+    pub fn take_key(&mut self, name: &str) -> bool {
+        match self.keys.get_mut(name) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                if *count == 0 {
+                    self.keys.remove(name);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use crate::hitbox::{HitBox, PlayerTouchedSomething};
+use crate::inventory::{Inventory, GENERIC_KEY};
+use crate::level_entity::LevelEntity;
+use crate::level_event::LevelEvent;
+use crate::level_schema::ItemData;
+use crate::player::{PlayerHealth, PlayerModifiers};
+use crate::state::GameState;
+use crate::story_flags::{FlagValue, StoryFlags};
+
+#[derive(Component)]
+pub struct Item {
+    pub item_type: String,
+    pub name: Option<String>,
+    /// Unique id ("<level_id>/<index>") used to remember pickups. None for items spawned
+    /// by a reaction, which aren't remembered: the reaction decides whether they come back.
+    pub id: Option<String>,
+}
+
+/// What picking up an item does
+#[derive(Debug, Clone)]
+pub enum ItemEffect {
+    Heal(i8),
+    /// Adds a key named after the item's `name` (or the generic "key")
+    GrantKey,
+    SpeedMultiplier(f32),
+    Shield(u8),
+    /// Adds to a numeric story flag (counters like coins)
+    AddToFlag(String, i32),
+    /// Sets a story flag (e.g. `lantern`, which door hints can check)
+    SetFlag(String, FlagValue),
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemDefinition {
+    pub glyph: String,
+    pub color: Color,
+    pub effects: Vec<ItemEffect>,
+}
+
+/// Maps `item_type` strings from level RON files to how the item looks and what it does
+#[derive(Resource)]
+pub struct ItemRegistry {
+    items: HashMap<String, ItemDefinition>,
+}
+
+impl ItemRegistry {
+    pub fn register(&mut self, item_type: &str, glyph: &str, color: Color, effects: Vec<ItemEffect>) {
+        self.items.insert(item_type.to_string(), ItemDefinition {
+            glyph: glyph.to_string(),
+            color,
+            effects,
+        });
+    }
+
+    pub fn get(&self, item_type: &str) -> Option<&ItemDefinition> {
+        self.items.get(item_type)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ItemDefinition)> {
+        self.items.iter()
+    }
+
+    /// Story flags that picking up an item can write (`AddToFlag`/`SetFlag`)
+    pub fn flag_keys(&self) -> impl Iterator<Item = &str> {
+        self.items.values().flat_map(|definition| &definition.effects).filter_map(|effect| match effect {
+            ItemEffect::AddToFlag(key, _) | ItemEffect::SetFlag(key, _) => Some(key.as_str()),
+            _ => None,
+        })
+    }
+}
+
+impl Default for ItemRegistry {
+    fn default() -> Self {
+        let mut registry = Self { items: HashMap::new() };
+        registry.register("key", "o-m", Color::srgb(0.9, 0.8, 0.2), vec![ItemEffect::GrantKey]);
+        registry.register("heart", "<3", Color::srgb(1.0, 0.2, 0.2), vec![ItemEffect::Heal(1)]);
+        registry.register("speed_boost", ">>", Color::srgb(0.3, 0.8, 1.0), vec![ItemEffect::SpeedMultiplier(1.25)]);
+        registry.register("shield", "[+]", Color::srgb(0.6, 0.6, 1.0), vec![ItemEffect::Shield(1)]);
+        registry.register("coin", "$", Color::srgb(1.0, 0.85, 0.1), vec![ItemEffect::AddToFlag("coins".to_string(), 1)]);
+        registry.register("lantern", "(*)", Color::srgb(1.0, 0.6, 0.2), vec![ItemEffect::SetFlag("lantern".to_string(), FlagValue::Bool(true))]);
+        registry
+    }
+}
+
+/// Items already picked up this run, so they don't respawn when a level is reloaded
+#[derive(Resource, Default)]
+pub struct CollectedItems(pub HashSet<String>);
+
+pub fn spawn_item_from_data(
+    commands: &mut Commands,
+    item_data: &ItemData,
+    id: Option<String>,
+    registry: &ItemRegistry,
+) -> Entity {
+    let (glyph, color) = match registry.get(&item_data.item_type) {
+        Some(definition) => (definition.glyph.clone(), definition.color),
+        None => {
+            warn!("Unknown item type: {}", item_data.item_type);
+            ("?".to_string(), Color::WHITE)
+        }
+    };
+
+    let entity = commands.spawn((
+        Text2d::new(glyph),
+        TextFont { font_size: 20.0, ..default() },
+        TextColor(color),
+        Transform::from_translation(Vec3::new(item_data.position.0, item_data.position.1, 1.0)),
+        Item {
+            item_type: item_data.item_type.clone(),
+            name: item_data.name.clone(),
+            id,
+        },
+        HitBox { width: 24.0, height: 24.0 },
+        LevelEntity,
+    )).id();
+
+    info!("Spawned item '{}' at ({}, {})", item_data.item_type, item_data.position.0, item_data.position.1);
+    entity
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_item_touch(
+    mut commands: Commands,
+    mut messages: MessageReader<PlayerTouchedSomething>,
+    items: Query<&Item>,
+    registry: Res<ItemRegistry>,
+    mut collected: ResMut<CollectedItems>,
+    mut health: ResMut<PlayerHealth>,
+    mut modifiers: ResMut<PlayerModifiers>,
+    mut inventory: ResMut<Inventory>,
+    mut story_flags: ResMut<StoryFlags>,
+    mut level_events: MessageWriter<LevelEvent>,
+) {
+    for message in messages.read() {
+        let Ok(item) = items.get(message.messaging_entity) else { continue };
+
+        if let Some(id) = &item.id {
+            collected.0.insert(id.clone());
+        }
+        commands.entity(message.messaging_entity).despawn();
+        info!("Picked up '{}'", item.item_type);
+        level_events.write(LevelEvent::ItemPicked { item_type: item.item_type.clone() });
+
+        let Some(definition) = registry.get(&item.item_type) else { continue };
+        for effect in &definition.effects {
+            match effect {
+                ItemEffect::Heal(amount) => {
+                    health.current = (health.current + amount).min(health.max);
+                }
+                ItemEffect::GrantKey => {
+                    inventory.add_key(item.name.as_deref().unwrap_or(GENERIC_KEY));
+                }
+                ItemEffect::SpeedMultiplier(multiplier) => {
+                    modifiers.speed_multiplier *= multiplier;
+                }
+                ItemEffect::Shield(amount) => {
+                    modifiers.shield += amount;
+                }
+                ItemEffect::AddToFlag(key, amount) => {
+                    let current = story_flags.get_number(key).unwrap_or(0);
+                    story_flags.set(key, FlagValue::Number(current + amount));
+                }
+                ItemEffect::SetFlag(key, value) => {
+                    story_flags.set(key, value.clone());
+                }
+            }
+        }
+    }
+}
+
+pub struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        // Items are spawned by level.rs from RON data
+        app.init_resource::<ItemRegistry>()
+           .init_resource::<CollectedItems>()
+           .add_systems(Update, handle_item_touch.run_if(
+               in_state(GameState::Playing).or(in_state(GameState::BossFight))
+           ));
+    }
+}
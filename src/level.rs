@@ -1,21 +1,26 @@
 use bevy::prelude::*;
-use mapgen::MapBuilder;
-use mapgen::filter::{
-    NoiseGenerator,
-    CellularAutomata,
-};
+use serde::{Deserialize, Serialize};
 use bevy_common_assets::ron::RonAssetPlugin;
 use crate::hitbox::HitBox;
 use crate::wall::Wall;
 use crate::level_entity::LevelEntity;
 use crate::player::Player;
-use crate::level_schema::{LevelData, NpcData};
+use crate::boss_schema::BossData;
+use crate::level_schema::{ConditionalDestination, DoorHint, LevelData, NpcData};
 use crate::npc::Npc;
 use crate::story_flags::StoryFlags;
+use crate::inventory::GENERIC_KEY;
+use crate::item::{CollectedItems, ItemRegistry, spawn_item_from_data};
+use crate::trigger_zone::spawn_trigger_zone_from_data;
+use crate::tile_map::{place_on_map, spawn_map_tiles};
+use crate::cave::{place_in_cave, spawn_cave_tiles};
 
-const TILE_SIZE: f32 = 32.0;
-const MAP_WIDTH: usize = 50;
-const MAP_HEIGHT: usize = 50;
+/// Level a new game starts in (and restarts from after defeat)
+pub const START_LEVEL: &str = "level_00_opening";
+/// Values `LevelData.room_type` understands
+pub const ROOM_TYPES: &[&str] = &["square", "cave", "cutscene", "boss", "map"];
+
+pub const TILE_SIZE: f32 = 32.0;
 
 #[derive(Component)]
 pub struct Floor;
@@ -23,6 +28,48 @@ pub struct Floor;
 #[derive(Component)]
 pub struct LevelDoor {
     pub leads_to: String,
+    pub leads_to_if: Vec<ConditionalDestination>,
+    pub label: String,
+    pub locked: bool,
+    pub key_required: Option<String>,
+    pub consume_key: bool,
+    pub show_label: bool,
+    pub hints: Vec<DoorHint>,
+}
+
+impl LevelDoor {
+    /// An unlocked door, used for doors spawned mid-level by reactions
+    pub fn open(leads_to: &str, label: &str) -> Self {
+        Self {
+            leads_to: leads_to.to_string(),
+            leads_to_if: Vec::new(),
+            label: label.to_string(),
+            locked: false,
+            key_required: None,
+            consume_key: false,
+            show_label: false,
+            hints: Vec::new(),
+        }
+    }
+
+    /// Level this door leads to right now, picking the first `leads_to_if` whose condition holds
+    pub fn destination(&self, story_flags: &StoryFlags) -> &str {
+        self.leads_to_if
+            .iter()
+            .find(|dest| story_flags.check(&dest.condition))
+            .map(|dest| dest.level.as_str())
+            .unwrap_or(&self.leads_to)
+    }
+
+    /// Hint to show right now: the first whose condition holds
+    pub fn hint(&self, story_flags: &StoryFlags) -> Option<&DoorHint> {
+        self.hints.iter().find(|hint| story_flags.check(&hint.condition))
+    }
+
+    /// Name of the key that opens this door (locked doors without `key_required` take any generic key)
+    pub fn required_key(&self) -> &str {
+        self.key_required.as_deref().unwrap_or(GENERIC_KEY)
+    }
 }
 
 // Resource to track current level and its data
@@ -36,29 +83,56 @@ pub struct CurrentLevel {
 impl Default for CurrentLevel {
     fn default() -> Self {
         Self {
-            level_id: "level_00_opening".to_string(),
+            level_id: START_LEVEL.to_string(),
             handle: Handle::default(),
             loaded: false,
         }
     }
 }
 
+/// Rooms visited this run, in order
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct RunHistory {
+    pub visited: Vec<String>,
+}
+
 // Resource to store level data once loaded
 #[derive(Resource, Default)]
 pub struct LoadedLevelData(pub Option<LevelData>);
 
-// Spawn level from data (called after asset is confirmed loaded)
+// Spawn level from data (called after asset is confirmed loaded), returning the player
 pub fn spawn_level_from_data_internal(
     commands: &mut Commands,
     level_data: &LevelData,
+    level_id: &str,
     windows: &Query<&Window>,
     story_flags: &StoryFlags,
-) {
+    item_registry: &ItemRegistry,
+    collected_items: &CollectedItems,
+) -> Entity {
     info!("Spawning level: {} ({})", level_data.name, level_data.room_type);
 
+    // Map and cave rooms decide where the player, doors (and for caves, NPCs and items) go
+    let mut placed = level_data.clone();
+    place_on_map(&mut placed);
+    let cave = place_in_cave(&mut placed);
+    let level_data = &placed;
+
     match level_data.room_type.as_str() {
-        "square" => spawn_border_walls_internal(commands, windows),
-        "cave" => spawn_cave_level(commands),
+        // Cutscene and boss rooms are plain square rooms too
+        "square" | "cutscene" | "boss" => spawn_border_walls_internal(commands, windows),
+        "cave" => {
+            if let Some(cave) = &cave {
+                spawn_cave_tiles(commands, cave);
+            }
+        }
+        "map" => match &level_data.map {
+            Some(map) => spawn_map_tiles(commands, map),
+            None => {
+                warn!("Map room '{}' has no map", level_data.id);
+                spawn_border_walls_internal(commands, windows);
+            }
+        },
         _ => {
             warn!("Unknown room type: {}", level_data.room_type);
             spawn_border_walls_internal(commands, windows);
@@ -75,9 +149,22 @@ pub fn spawn_level_from_data_internal(
         spawn_npc_from_data(commands, npc_data, story_flags);
     }
 
+    // Spawn items that haven't been picked up yet
+    for (index, item_data) in level_data.items.iter().enumerate() {
+        let id = format!("{}/{}", level_id, index);
+        if collected_items.0.contains(&id) {
+            continue;
+        }
+        spawn_item_from_data(commands, item_data, Some(id), item_registry);
+    }
+
+    for zone_data in &level_data.zones {
+        spawn_trigger_zone_from_data(commands, zone_data);
+    }
+
     // Spawn player at level's start position
     let start_pos = Vec3::new(level_data.player_start.0, level_data.player_start.1, 2.0);
-    commands.spawn((
+    let player = commands.spawn((
         Text2d::new("@"),
         TextFont { font_size: 24.0, ..default() },
         TextColor(Color::WHITE),
@@ -85,8 +172,9 @@ pub fn spawn_level_from_data_internal(
         Player,
         LevelEntity,
         HitBox { width: 24.0, height: 24.0 },
-    ));
+    )).id();
     info!("Spawned player at ({}, {})", level_data.player_start.0, level_data.player_start.1);
+    player
 }
 
 
@@ -115,40 +203,18 @@ fn spawn_border_walls_internal(commands: &mut Commands, windows: &Query<&Window>
     }
 }
 
-fn spawn_cave_level(commands: &mut Commands) {
-    let map = MapBuilder::new(MAP_WIDTH, MAP_HEIGHT)
-        .with(NoiseGenerator::uniform())
-        .with(CellularAutomata::new())
-        .build();
-
-    let offset_x = (MAP_WIDTH as f32 * TILE_SIZE) / 2.0;
-    let offset_y = (MAP_HEIGHT as f32 * TILE_SIZE) / 2.0;
-
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH {
-            let pos = Vec3::new(
-                x as f32 * TILE_SIZE - offset_x,
-                y as f32 * TILE_SIZE - offset_y,
-                0.0,
-            );
-
-            if map.is_walkable(x, y) {
-                commands.spawn((
-                    Text2d::new("."),
-                    TextFont { font_size: TILE_SIZE, ..default() },
-                    TextColor(Color::srgb(0.3, 0.3, 0.3)),
-                    Transform::from_translation(pos),
-                    Floor,
-                    LevelEntity,
-                ));
-            } else {
-                spawn_wall_at(commands, pos, TILE_SIZE);
-            }
-        }
-    }
+pub fn spawn_floor_at(commands: &mut Commands, pos: Vec3) {
+    commands.spawn((
+        Text2d::new("."),
+        TextFont { font_size: TILE_SIZE, ..default() },
+        TextColor(Color::srgb(0.3, 0.3, 0.3)),
+        Transform::from_translation(pos),
+        Floor,
+        LevelEntity,
+    ));
 }
 
-pub fn spawn_wall_at(commands: &mut Commands, pos: Vec3, size: f32) {
+pub fn spawn_wall_at(commands: &mut Commands, pos: Vec3, size: f32) -> Entity {
     commands.spawn((
         Text2d::new("#"),
         TextFont { font_size: size, ..default() },
@@ -157,10 +223,22 @@ pub fn spawn_wall_at(commands: &mut Commands, pos: Vec3, size: f32) {
         Wall,
         HitBox { width: size, height: size },
         LevelEntity,
-    ));
+    )).id()
+}
+
+/// Wall tiles in a straight line from `from` to `to`, both ends included
+pub fn spawn_wall_segment(commands: &mut Commands, from: (f32, f32), to: (f32, f32)) -> Vec<Entity> {
+    let (start, end) = (Vec2::new(from.0, from.1), Vec2::new(to.0, to.1));
+    let steps = (start.distance(end) / TILE_SIZE).round() as u32;
+    (0..=steps)
+        .map(|i| {
+            let t = if steps == 0 { 0.0 } else { i as f32 / steps as f32 };
+            spawn_wall_at(commands, start.lerp(end, t).extend(0.0), TILE_SIZE)
+        })
+        .collect()
 }
 
-fn spawn_door_from_data(commands: &mut Commands, door_data: &crate::level_schema::DoorData) {
+pub fn spawn_door_from_data(commands: &mut Commands, door_data: &crate::level_schema::DoorData) -> Entity {
     use crate::art::DOOR_ART;
     use crate::level_schema::EntityComponent;
     use crate::roaming::Roam;
@@ -170,7 +248,16 @@ fn spawn_door_from_data(commands: &mut Commands, door_data: &crate::level_schema
         TextFont { font_size: 6.0, ..default() },
         TextColor(Color::WHITE),
         Transform::from_translation(Vec3::new(door_data.position.0, door_data.position.1, 1.0)),
-        LevelDoor { leads_to: door_data.leads_to.clone() },
+        LevelDoor {
+            leads_to: door_data.leads_to.clone(),
+            leads_to_if: door_data.leads_to_if.clone(),
+            label: door_data.label.clone(),
+            locked: door_data.locked,
+            key_required: door_data.key_required.clone(),
+            consume_key: door_data.consume_key,
+            show_label: door_data.show_label,
+            hints: door_data.hints.clone(),
+        },
         HitBox { width: 80.0, height: 120.0 },
         LevelEntity,
     )).id();
@@ -191,20 +278,24 @@ fn spawn_door_from_data(commands: &mut Commands, door_data: &crate::level_schema
         }
     }
 
-    info!("Spawned door '{}' at ({}, {})", door_data.label, door_data.position.0, door_data.position.1);
+    if door_data.locked {
+        info!("Spawned locked door '{}' at ({}, {}) (key: {})", door_data.label, door_data.position.0, door_data.position.1,
+            door_data.key_required.as_deref().unwrap_or(GENERIC_KEY));
+    } else {
+        info!("Spawned door '{}' at ({}, {})", door_data.label, door_data.position.0, door_data.position.1);
+    }
+    entity
 }
 
-fn spawn_npc_from_data(commands: &mut Commands, npc_data: &NpcData, story_flags: &StoryFlags) {
+/// None if the NPC's `<name>_present` flag says they're gone
+pub fn spawn_npc_from_data(commands: &mut Commands, npc_data: &NpcData, story_flags: &StoryFlags) -> Option<Entity> {
     use crate::art::DUCK;
-    use crate::level_schema::EntityComponent;
-    use crate::roaming::Roam;
-    use crate::follow::Follow;
 
     // Check if this NPC should spawn based on story flags
     let present_key = format!("{}_present", npc_data.name.to_lowercase());
     if let Some(false) = story_flags.get_bool(&present_key) {
         info!("NPC '{}' is not present, not spawning", npc_data.name);
-        return;
+        return None;
     }
 
     let art = match npc_data.name.as_str() {
@@ -222,8 +313,19 @@ fn spawn_npc_from_data(commands: &mut Commands, npc_data: &NpcData, story_flags:
         LevelEntity,
     )).id();
 
-    // Add extra components from RON data
-    for component in &npc_data.extra {
+    insert_extra_components(commands, entity, &npc_data.extra);
+
+    info!("Spawned NPC '{}' at ({}, {})", npc_data.name, npc_data.position.0, npc_data.position.1);
+    Some(entity)
+}
+
+/// Add `extra` components from RON data to an entity
+pub fn insert_extra_components(commands: &mut Commands, entity: Entity, extra: &[crate::level_schema::EntityComponent]) {
+    use crate::level_schema::EntityComponent;
+    use crate::roaming::Roam;
+    use crate::follow::Follow;
+
+    for component in extra {
         match component {
             EntityComponent::Roam { speed, range } => {
                 commands.entity(entity).insert(Roam {
@@ -241,8 +343,6 @@ fn spawn_npc_from_data(commands: &mut Commands, npc_data: &NpcData, story_flags:
             }
         }
     }
-
-    info!("Spawned NPC '{}' at ({}, {})", npc_data.name, npc_data.position.0, npc_data.position.1);
 }
 
 pub struct LevelPlugin;
@@ -250,8 +350,13 @@ pub struct LevelPlugin;
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(RonAssetPlugin::<LevelData>::new(&["ron"]))
+           .add_plugins(RonAssetPlugin::<BossData>::new(&["boss.ron"]))
            .init_resource::<CurrentLevel>()
-           .init_resource::<LoadedLevelData>();
+           .init_resource::<LoadedLevelData>()
+           .init_resource::<RunHistory>();
         // Level loading and spawning is handled by loading_new_level.rs
+
+        #[cfg(feature = "dev")]
+        app.add_plugins(crate::level_reload::LevelReloadPlugin);
     }
 }
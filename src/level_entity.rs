@@ -2,4 +2,8 @@ use bevy::prelude::*;
 
 //Noting what things are part ofa level so they can be despawned.
 #[derive(Component)]
-pub struct LevelEntity;
\ No newline at end of file
+pub struct LevelEntity;
+
+/// Name that `DespawnTagged`/`ModifyTagged` actions find an entity by
+#[derive(Component)]
+pub struct Tagged(pub String);
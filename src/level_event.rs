@@ -1,8 +1,76 @@
 use bevy::prelude::*;
 
+/// Something that happened in the room, for its `reactions` to respond to
 #[derive(Message, Clone, Debug)]
 pub enum LevelEvent {
+    /// The boss fired its last volley of the phase
     ProjectilesDone,
+    /// A dialogue (the room's own or a queued one) ran out of lines
     DialogueComplete,
+    /// ProjectilesDone in the boss's last phase
     BossDefeated,
+    /// The room finished spawning
+    LevelEntered,
+    DoorOpened { label: String },
+    ItemPicked { item_type: String },
+    NpcDied { name: String },
+    /// The player stepped into a trigger zone (`LevelData.zones`)
+    ZoneEntered { name: String },
+    ZoneLeft { name: String },
+}
+
+impl LevelEvent {
+    /// Every event name, as used in level files
+    pub const NAMES: &'static [&'static str] = &[
+        "ProjectilesDone",
+        "DialogueComplete",
+        "BossDefeated",
+        "LevelEntered",
+        "DoorOpened",
+        "ItemPicked",
+        "NpcDied",
+        "ZoneEntered",
+        "ZoneLeft",
+    ];
+
+    /// Name used for this event in level files (`Trigger::Event`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            LevelEvent::ProjectilesDone => "ProjectilesDone",
+            LevelEvent::DialogueComplete => "DialogueComplete",
+            LevelEvent::BossDefeated => "BossDefeated",
+            LevelEvent::LevelEntered => "LevelEntered",
+            LevelEvent::DoorOpened { .. } => "DoorOpened",
+            LevelEvent::ItemPicked { .. } => "ItemPicked",
+            LevelEvent::NpcDied { .. } => "NpcDied",
+            LevelEvent::ZoneEntered { .. } => "ZoneEntered",
+            LevelEvent::ZoneLeft { .. } => "ZoneLeft",
+        }
+    }
+
+    /// Door label, item type, NPC or zone name, for events that carry one (`Trigger::EventWith`)
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            LevelEvent::DoorOpened { label } => Some(label),
+            LevelEvent::ItemPicked { item_type } => Some(item_type),
+            LevelEvent::NpcDied { name } | LevelEvent::ZoneEntered { name } | LevelEvent::ZoneLeft { name } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Build an event from its name; `value` is required for events that carry one
+    pub fn from_name(name: &str, value: Option<&str>) -> Option<Self> {
+        match (name, value) {
+            ("ProjectilesDone", _) => Some(LevelEvent::ProjectilesDone),
+            ("DialogueComplete", _) => Some(LevelEvent::DialogueComplete),
+            ("BossDefeated", _) => Some(LevelEvent::BossDefeated),
+            ("LevelEntered", _) => Some(LevelEvent::LevelEntered),
+            ("DoorOpened", Some(label)) => Some(LevelEvent::DoorOpened { label: label.to_string() }),
+            ("ItemPicked", Some(item_type)) => Some(LevelEvent::ItemPicked { item_type: item_type.to_string() }),
+            ("NpcDied", Some(name)) => Some(LevelEvent::NpcDied { name: name.to_string() }),
+            ("ZoneEntered", Some(name)) => Some(LevelEvent::ZoneEntered { name: name.to_string() }),
+            ("ZoneLeft", Some(name)) => Some(LevelEvent::ZoneLeft { name: name.to_string() }),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use crate::level::START_LEVEL;
+use crate::level_index::{LevelFile, LinkKind, describe_condition, level_links};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz (`dot -Tsvg levels.dot > levels.svg`)
+    Dot,
+    /// Mermaid flowchart (renders in GitHub markdown)
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dot" => Some(GraphFormat::Dot),
+            "mermaid" => Some(GraphFormat::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+struct Node {
+    id: String,
+    title: String,
+    boss: bool,
+    start: bool,
+    /// Referenced but has no file, or the file doesn't parse
+    problem: Option<&'static str>,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    lines: Vec<String>,
+    style: EdgeStyle,
+    locked: bool,
+}
+
+#[derive(PartialEq)]
+enum EdgeStyle {
+    Door,
+    /// `leads_to_if` branch
+    Conditional,
+    /// Reaction or dialogue choice action
+    Action,
+}
+
+/// Rooms and the ways between them, as a DOT or Mermaid graph
+pub fn render_graph(levels: &[LevelFile], format: GraphFormat) -> String {
+    let (nodes, edges) = build_graph(levels);
+    match format {
+        GraphFormat::Dot => render_dot(&nodes, &edges),
+        GraphFormat::Mermaid => render_mermaid(&nodes, &edges),
+    }
+}
+
+fn build_graph(levels: &[LevelFile]) -> (Vec<Node>, Vec<Edge>) {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut known = BTreeSet::new();
+    let mut targets = BTreeSet::new();
+
+    for level in levels {
+        known.insert(level.file_id.clone());
+        let Ok(data) = &level.data else {
+            nodes.push(Node {
+                id: level.file_id.clone(),
+                title: level.file_id.clone(),
+                boss: false,
+                start: level.file_id == START_LEVEL,
+                problem: Some("broken"),
+            });
+            continue;
+        };
+
+        nodes.push(Node {
+            id: level.file_id.clone(),
+            title: data.name.clone(),
+            boss: data.room_type == "boss" || data.boss.is_some(),
+            start: level.file_id == START_LEVEL,
+            problem: None,
+        });
+
+        for link in level_links(data) {
+            let mut lines = vec![link.label.clone()];
+            if let Some(key) = &link.key {
+                lines.push(format!("locked ({})", key));
+            }
+            if let Some(condition) = &link.condition {
+                lines.push(format!("if {}", describe_condition(condition)));
+            }
+            let style = match (link.kind, link.condition.is_some()) {
+                (LinkKind::Action, _) => EdgeStyle::Action,
+                (LinkKind::Door, true) => EdgeStyle::Conditional,
+                (LinkKind::Door, false) => EdgeStyle::Door,
+            };
+            targets.insert(link.to.clone());
+            edges.push(Edge { from: level.file_id.clone(), to: link.to, lines, style, locked: link.key.is_some() });
+        }
+    }
+
+    for missing in targets.difference(&known) {
+        nodes.push(Node {
+            id: missing.clone(),
+            title: missing.clone(),
+            boss: false,
+            start: false,
+            problem: Some("missing"),
+        });
+    }
+
+    (nodes, edges)
+}
+
+fn render_dot(nodes: &[Node], edges: &[Edge]) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = String::from("digraph levels {\n    rankdir=LR;\n    node [shape=box, fontname=\"monospace\"];\n\n");
+
+    for node in nodes {
+        let mut label = escape(&node.title);
+        if node.title != node.id {
+            label.push_str(&format!("\\n{}", escape(&node.id)));
+        }
+        let mut attrs = Vec::new();
+        if let Some(problem) = node.problem {
+            label.push_str(&format!("\\n({})", problem));
+            attrs.push("style=dashed, color=gray".to_string());
+        } else if node.boss {
+            attrs.push("shape=doubleoctagon, color=red".to_string());
+        }
+        if node.start {
+            attrs.push("penwidth=3".to_string());
+        }
+        attrs.insert(0, format!("label=\"{}\"", label));
+        out.push_str(&format!("    \"{}\" [{}];\n", escape(&node.id), attrs.join(", ")));
+    }
+    out.push('\n');
+
+    for edge in edges {
+        let label: Vec<String> = edge.lines.iter().map(|line| escape(line)).collect();
+        let mut attrs = vec![format!("label=\"{}\"", label.join("\\n"))];
+        match edge.style {
+            EdgeStyle::Door => {}
+            EdgeStyle::Conditional => attrs.push("style=dashed".to_string()),
+            EdgeStyle::Action => attrs.push("style=dotted".to_string()),
+        }
+        if edge.locked {
+            attrs.push("color=goldenrod".to_string());
+        }
+        out.push_str(&format!(
+            "    \"{}\" -> \"{}\" [{}];\n",
+            escape(&edge.from),
+            escape(&edge.to),
+            attrs.join(", ")
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(nodes: &[Node], edges: &[Edge]) -> String {
+    // Mermaid ids can't contain most punctuation; labels carry the real names. `_` doubles and
+    // anything else becomes `_<hex>_`, so different level ids never share a node.
+    let node_id = |id: &str| {
+        id.chars()
+            .map(|c| match c {
+                '_' => "__".to_string(),
+                c if c.is_ascii_alphanumeric() => c.to_string(),
+                c => format!("_{:x}_", c as u32),
+            })
+            .collect::<String>()
+    };
+    let escape = |text: &str| text.replace('"', "#quot;");
+    let mut out = String::from("flowchart LR\n");
+
+    for node in nodes {
+        let mut label = escape(&node.title);
+        if node.title != node.id {
+            label.push_str(&format!("<br/>{}", escape(&node.id)));
+        }
+        if let Some(problem) = node.problem {
+            label.push_str(&format!("<br/>({})", problem));
+        }
+        let id = node_id(&node.id);
+        if node.boss && node.problem.is_none() {
+            out.push_str(&format!("    {}{{{{\"{}\"}}}}\n", id, label));
+        } else {
+            out.push_str(&format!("    {}[\"{}\"]\n", id, label));
+        }
+    }
+
+    for edge in edges {
+        let label: Vec<String> = edge.lines.iter().map(|line| escape(line)).collect();
+        let arrow = match edge.style {
+            EdgeStyle::Door => "-->",
+            EdgeStyle::Conditional | EdgeStyle::Action => "-.->",
+        };
+        out.push_str(&format!(
+            "    {} {}|\"{}\"| {}\n",
+            node_id(&edge.from),
+            arrow,
+            label.join("<br/>"),
+            node_id(&edge.to)
+        ));
+    }
+
+    let class_members = |filter: &dyn Fn(&Node) -> bool| {
+        nodes.iter().filter(|node| filter(node)).map(|node| node_id(&node.id)).collect::<Vec<_>>()
+    };
+    let classes: [(&str, &str, Vec<String>); 3] = [
+        ("boss", "fill:#fdd,stroke:#c00", class_members(&|node| node.boss && node.problem.is_none())),
+        ("problem", "stroke-dasharray:5 5,color:#888", class_members(&|node| node.problem.is_some())),
+        ("start", "stroke-width:3px", class_members(&|node| node.start)),
+    ];
+    for (name, style, members) in classes {
+        if members.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("    classDef {} {}\n", name, style));
+        out.push_str(&format!("    class {} {}\n", members.join(","), name));
+    }
+    out
+}
@@ -0,0 +1,259 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::inventory::GENERIC_KEY;
+use crate::level_schema::{Action, Condition, DialogueLine, EntityData, LevelData, Trigger};
+use crate::story_flags::FlagValue;
+
+/// A level file read straight from disk (for tools; the game loads levels through the AssetServer)
+pub struct LevelFile {
+    /// File name without `.ron`, which is what doors and actions refer to
+    pub file_id: String,
+    pub path: PathBuf,
+    pub data: Result<LevelData, String>,
+}
+
+/// Every `*.ron` in a levels directory, sorted by file name
+pub fn read_levels(levels_dir: &Path) -> io::Result<Vec<LevelFile>> {
+    let mut levels = Vec::new();
+    for entry in fs::read_dir(levels_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("ron") {
+            continue;
+        }
+        let Some(file_id) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string) else { continue };
+        let data = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| ron::from_str::<LevelData>(&text).map_err(|err| err.to_string()));
+        levels.push(LevelFile { file_id, path, data });
+    }
+    levels.sort_by(|a, b| a.file_id.cmp(&b.file_id));
+    Ok(levels)
+}
+
+/// One way out of a level
+pub struct LevelLink {
+    pub to: String,
+    /// What leads there, e.g. "door 'Left Door'"
+    pub via: String,
+    /// Short name for graphs: the door label, or the action that does it
+    pub label: String,
+    pub kind: LinkKind,
+    /// Key that opens it, for locked doors
+    pub key: Option<String>,
+    /// Only taken when this holds (`leads_to_if`)
+    pub condition: Option<Condition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A door in the level file
+    Door,
+    /// A door, transition or next level set by a reaction or dialogue choice
+    Action,
+}
+
+/// Every level this one can send the player to: doors (including `leads_to_if`) and actions
+pub fn level_links(level: &LevelData) -> Vec<LevelLink> {
+    let mut links = Vec::new();
+    for door in &level.doors {
+        let via = format!("door '{}'", door.label);
+        let key = door.locked.then(|| door.key_required.clone().unwrap_or_else(|| GENERIC_KEY.to_string()));
+        for conditional in &door.leads_to_if {
+            links.push(LevelLink {
+                to: conditional.level.clone(),
+                via: format!("{} (leads_to_if)", via),
+                label: door.label.clone(),
+                kind: LinkKind::Door,
+                key: key.clone(),
+                condition: Some(conditional.condition.clone()),
+            });
+        }
+        links.push(LevelLink {
+            to: door.leads_to.clone(),
+            via,
+            label: door.label.clone(),
+            kind: LinkKind::Door,
+            key,
+            condition: None,
+        });
+    }
+    for_each_action(level, &mut |action, context| {
+        let (to, label) = match action {
+            Action::SpawnDoor { leads_to, label, .. } => (leads_to, format!("SpawnDoor '{}'", label)),
+            Action::SpawnEntity { entity: EntityData::Door(door), .. } => {
+                let label = format!("SpawnEntity '{}'", door.label);
+                let key = door.locked.then(|| door.key_required.clone().unwrap_or_else(|| GENERIC_KEY.to_string()));
+                let destinations = door.leads_to_if
+                    .iter()
+                    .map(|conditional| (&conditional.level, Some(conditional.condition.clone())))
+                    .chain([(&door.leads_to, None)]);
+                for (to, condition) in destinations {
+                    links.push(LevelLink {
+                        to: to.clone(),
+                        via: format!("{} in {}", label, context),
+                        label: label.clone(),
+                        kind: LinkKind::Action,
+                        key: key.clone(),
+                        condition,
+                    });
+                }
+                return;
+            }
+            Action::TransitionToLevel { level_id } => (level_id, "TransitionToLevel".to_string()),
+            Action::SetNextLevel { level_id } => (level_id, "SetNextLevel".to_string()),
+            _ => return,
+        };
+        links.push(LevelLink {
+            to: to.clone(),
+            via: format!("{} in {}", label, context),
+            label,
+            kind: LinkKind::Action,
+            key: None,
+            condition: None,
+        });
+    });
+    links
+}
+
+/// Short readable form of a condition, e.g. `duck_status = alive and coins > 2`
+pub fn describe_condition(condition: &Condition) -> String {
+    let value = |value: &FlagValue| match value {
+        FlagValue::Bool(b) => b.to_string(),
+        FlagValue::Text(text) => text.clone(),
+        FlagValue::Number(n) => n.to_string(),
+    };
+    let group = |conditions: &[Condition], joiner: &str| {
+        let parts: Vec<String> = conditions.iter().map(describe_condition).collect();
+        format!("({})", parts.join(joiner))
+    };
+    match condition {
+        Condition::Always => "always".to_string(),
+        Condition::Equals(key, v) => format!("{} = {}", key, value(v)),
+        Condition::NotEquals(key, v) => format!("{} != {}", key, value(v)),
+        Condition::GreaterThan(key, n) => format!("{} > {}", key, n),
+        Condition::AtLeast(key, n) => format!("{} >= {}", key, n),
+        Condition::LessThan(key, n) => format!("{} < {}", key, n),
+        Condition::Has(key) => format!("has {}", key),
+        Condition::All(conditions) => group(conditions, " and "),
+        Condition::Any(conditions) => group(conditions, " or "),
+        Condition::Not(condition) => format!("not ({})", describe_condition(condition)),
+    }
+}
+
+/// Visit every action in a level (reactions, dialogue choices, and dialogue they queue),
+/// with a short description of where it lives
+pub fn for_each_action(level: &LevelData, visit: &mut impl FnMut(&Action, &str)) {
+    for (i, reaction) in level.reactions.iter().enumerate() {
+        let context = format!("reaction {}", i + 1);
+        for action in &reaction.actions {
+            visit_action(action, &context, visit);
+        }
+    }
+    visit_line_actions(&level.dialogue, "dialogue", visit);
+}
+
+fn visit_action(action: &Action, context: &str, visit: &mut impl FnMut(&Action, &str)) {
+    visit(action, context);
+    match action {
+        Action::QueueDialogue { lines, .. } => {
+            visit_line_actions(lines, &format!("{} (queued dialogue)", context), visit);
+        }
+        Action::After { actions, .. } | Action::Repeat { actions, .. } => {
+            for nested in actions {
+                visit_action(nested, context, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn visit_line_actions(lines: &[DialogueLine], context: &str, visit: &mut impl FnMut(&Action, &str)) {
+    for (i, line) in lines.iter().enumerate() {
+        for choice in &line.choices {
+            let choice_context = format!("{} line {} choice '{}'", context, i + 1, choice.text);
+            for action in &choice.actions {
+                visit_action(action, &choice_context, visit);
+            }
+        }
+    }
+}
+
+/// Every dialogue line in a level, including lines queued by actions
+pub fn all_dialogue_lines(level: &LevelData) -> Vec<&DialogueLine> {
+    let mut lines: Vec<&DialogueLine> = level.dialogue.iter().collect();
+    fn queued<'a>(actions: &'a [Action], lines: &mut Vec<&'a DialogueLine>) {
+        for action in actions {
+            match action {
+                Action::QueueDialogue { lines: queued_lines, .. } => {
+                    for line in queued_lines {
+                        lines.push(line);
+                        for choice in &line.choices {
+                            queued(&choice.actions, lines);
+                        }
+                    }
+                }
+                Action::After { actions, .. } | Action::Repeat { actions, .. } => queued(actions, lines),
+                _ => {}
+            }
+        }
+    }
+    for reaction in &level.reactions {
+        queued(&reaction.actions, &mut lines);
+    }
+    for line in &level.dialogue {
+        for choice in &line.choices {
+            queued(&choice.actions, &mut lines);
+        }
+    }
+    lines
+}
+
+/// Flag keys a condition reads
+pub fn condition_flags(condition: &Condition, keys: &mut Vec<String>) {
+    match condition {
+        Condition::Always => {}
+        Condition::Equals(key, _)
+        | Condition::NotEquals(key, _)
+        | Condition::GreaterThan(key, _)
+        | Condition::AtLeast(key, _)
+        | Condition::LessThan(key, _)
+        | Condition::Has(key) => keys.push(key.clone()),
+        Condition::All(conditions) | Condition::Any(conditions) => {
+            for condition in conditions {
+                condition_flags(condition, keys);
+            }
+        }
+        Condition::Not(condition) => condition_flags(condition, keys),
+    }
+}
+
+/// Events a trigger listens for, with the value `EventWith` asks for
+pub fn trigger_events<'a>(trigger: &'a Trigger, events: &mut Vec<(&'a str, Option<&'a str>)>) {
+    match trigger {
+        Trigger::Event(event) | Trigger::EventAndFlag { event, .. } => events.push((event, None)),
+        Trigger::EventWith { event, value } => events.push((event, Some(value))),
+        Trigger::All(triggers) | Trigger::Any(triggers) => {
+            for trigger in triggers {
+                trigger_events(trigger, events);
+            }
+        }
+        Trigger::Not(trigger) => trigger_events(trigger, events),
+        Trigger::Flag(_) | Trigger::HealthBelow(_) | Trigger::HealthAtLeast(_) | Trigger::HasKey(_) | Trigger::NpcPresent(_) => {}
+    }
+}
+
+/// Flag keys a trigger reads
+pub fn trigger_flags(trigger: &Trigger, keys: &mut Vec<String>) {
+    match trigger {
+        Trigger::EventAndFlag { flag, .. } => keys.push(flag.clone()),
+        Trigger::Flag(condition) => condition_flags(condition, keys),
+        Trigger::All(triggers) | Trigger::Any(triggers) => {
+            for trigger in triggers {
+                trigger_flags(trigger, keys);
+            }
+        }
+        Trigger::Not(trigger) => trigger_flags(trigger, keys),
+        Trigger::Event(_) | Trigger::EventWith { .. } | Trigger::HealthBelow(_) | Trigger::HealthAtLeast(_) | Trigger::HasKey(_) | Trigger::NpcPresent(_) => {}
+    }
+}
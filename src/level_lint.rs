@@ -0,0 +1,500 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::boss_schema::BossData;
+use crate::cave::MIN_CAVE_SIZE;
+use crate::item::{ItemEffect, ItemRegistry};
+use crate::level::{ROOM_TYPES, START_LEVEL};
+use crate::level_event::LevelEvent;
+use crate::level_index::{
+    LevelFile, all_dialogue_lines, condition_flags, for_each_action, level_links, read_levels, trigger_events, trigger_flags,
+};
+use crate::level_schema::{Action, Condition, DialogueLine, DoorHint, LastingEffect, LevelData, RoomEffect, END_LABEL};
+use crate::run::{RUN_DEPTH_FLAG, RunFile, read_runs};
+use crate::run_schema::RunData;
+use crate::state::boss_fight::ARENA_TAG;
+use crate::tile_map::{DOOR, MAP_TILES, PLAYER, TileMap};
+use crate::state::GameState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Will break at runtime (hang, wrong room, silent no-op)
+    Error,
+    /// Probably a mistake, but the game copes
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// File the issue is in, relative to the assets directory
+    pub file: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.file, self.message)
+    }
+}
+
+struct Linter<'a> {
+    assets_dir: &'a Path,
+    issues: Vec<LintIssue>,
+}
+
+impl Linter<'_> {
+    fn error(&mut self, file: &str, message: String) {
+        self.issues.push(LintIssue { severity: Severity::Error, file: file.to_string(), message });
+    }
+
+    fn warning(&mut self, file: &str, message: String) {
+        self.issues.push(LintIssue { severity: Severity::Warning, file: file.to_string(), message });
+    }
+}
+
+/// Check every level in `<assets_dir>/levels`, the files they point at, and the runs in `<assets_dir>/runs`
+pub fn lint_assets(assets_dir: &Path) -> io::Result<Vec<LintIssue>> {
+    let levels = read_levels(&assets_dir.join("levels"))?;
+    let mut linter = Linter { assets_dir, issues: Vec::new() };
+
+    let file_ids: HashSet<&str> = levels.iter().map(|level| level.file_id.as_str()).collect();
+    let flags_set = flags_set(&levels);
+    let mut ids: HashMap<&str, &str> = HashMap::new();
+
+    for level in &levels {
+        let file = format!("levels/{}.ron", level.file_id);
+        let data = match &level.data {
+            Ok(data) => data,
+            Err(err) => {
+                linter.error(&file, format!("does not parse: {}", err));
+                continue;
+            }
+        };
+
+        if data.id != level.file_id {
+            linter.warning(&file, format!("id '{}' doesn't match the file name", data.id));
+        }
+        if let Some(other) = ids.insert(&data.id, &level.file_id) {
+            linter.error(&file, format!("id '{}' is also used by levels/{}.ron", data.id, other));
+        }
+
+        lint_level(&mut linter, &file, data, &file_ids, &flags_set);
+    }
+
+    let bosses: BTreeSet<&str> = levels.iter()
+        .filter_map(|level| level.data.as_ref().ok()?.boss.as_deref())
+        .collect();
+    for boss in bosses {
+        let path = boss_path(boss);
+        // A missing file was already reported by each level that names it
+        let Ok(text) = fs::read_to_string(assets_dir.join(&path)) else { continue };
+        match ron::from_str::<BossData>(&text) {
+            Ok(data) => lint_boss(&mut linter, &path, &data),
+            Err(err) => linter.error(&path, format!("does not parse: {}", err)),
+        }
+    }
+
+    let runs = read_runs(&assets_dir.join("runs"))?;
+    for run in &runs {
+        let file = format!("runs/{}.run.ron", run.file_id);
+        match &run.data {
+            Ok(data) => lint_run(&mut linter, &file, data, &file_ids),
+            Err(err) => linter.error(&file, format!("does not parse: {}", err)),
+        }
+    }
+
+    lint_reachability(&mut linter, &levels, &runs);
+    Ok(linter.issues)
+}
+
+fn boss_path(boss: &str) -> String {
+    format!("bosses/{}.boss.ron", boss)
+}
+
+fn lint_level(
+    linter: &mut Linter,
+    file: &str,
+    level: &LevelData,
+    file_ids: &HashSet<&str>,
+    flags_set: &HashSet<String>,
+) {
+    if !ROOM_TYPES.contains(&level.room_type.as_str()) {
+        linter.error(file, format!("unknown room_type '{}' (expected one of {})", level.room_type, ROOM_TYPES.join(", ")));
+    }
+
+    for link in level_links(level) {
+        if !file_ids.contains(link.to.as_str()) {
+            linter.error(file, format!("{} leads to '{}', which has no level file", link.via, link.to));
+        }
+    }
+
+    if let Some(track) = &level.music {
+        let path = format!("sounds/music/{}.mp3", track);
+        if !linter.assets_dir.join(&path).exists() {
+            // The game just stays silent, so this doesn't fail the lint
+            linter.warning(file, format!("music '{}' not found ({})", track, path));
+        }
+    }
+
+    // The boss file itself is checked once in lint_assets, however many levels use it
+    if let Some(boss) = &level.boss {
+        let path = boss_path(boss);
+        if !linter.assets_dir.join(&path).exists() {
+            linter.error(file, format!("boss '{}' not found ({})", boss, path));
+        }
+    }
+
+    lint_map(linter, file, level);
+    lint_cave(linter, file, level);
+
+    let mut zone_names = HashSet::new();
+    for zone in &level.zones {
+        if !zone_names.insert(zone.name.as_str()) {
+            linter.warning(file, format!("more than one zone is named '{}'", zone.name));
+        }
+        if zone.size.0 <= 0.0 || zone.size.1 <= 0.0 {
+            linter.error(file, format!("zone '{}' has size {:?}, so the player can never enter it", zone.name, zone.size));
+        }
+    }
+
+    for (i, reaction) in level.reactions.iter().enumerate() {
+        let mut events = Vec::new();
+        trigger_events(&reaction.trigger, &mut events);
+        if events.is_empty() {
+            linter.warning(file, format!("reaction {} has no Event in its trigger, so any event fires it", i + 1));
+        }
+        for (event, value) in events {
+            if !LevelEvent::NAMES.contains(&event) {
+                linter.error(file, format!("reaction {} listens for unknown event '{}'", i + 1, event));
+            }
+            if let Some(zone) = value
+                && matches!(event, "ZoneEntered" | "ZoneLeft")
+                && !zone_names.contains(zone)
+            {
+                linter.error(file, format!("reaction {} listens for zone '{}', which isn't in this level", i + 1, zone));
+            }
+        }
+    }
+
+    let mut bad_actions = Vec::new();
+    let mut idle_repeats = Vec::new();
+    let mut queued_dialogues = Vec::new();
+    let mut tags_spawned: HashSet<String> = HashSet::new();
+    let mut tags_used = Vec::new();
+    if level.room_type == "boss" || level.boss.is_some() {
+        tags_spawned.insert(ARENA_TAG.to_string());
+    }
+    for_each_action(level, &mut |action, context| {
+        match action {
+            Action::SpawnEntity { tag: Some(tag), .. } => {
+                tags_spawned.insert(tag.clone());
+            }
+            Action::DespawnTagged { tag } | Action::ModifyTagged { tag, .. } => {
+                tags_used.push((context.to_string(), tag.clone()));
+            }
+            Action::QueueDialogue { lines, then } => {
+                if GameState::from_name(then).is_none() {
+                    bad_actions.push(format!("QueueDialogue in {} goes to unknown state '{}'", context, then));
+                }
+                queued_dialogues.push((context.to_string(), lines.clone()));
+            }
+            Action::Wait { seconds } if !valid_delay(*seconds) => {
+                bad_actions.push(format!("Wait in {} waits {} seconds", context, seconds));
+            }
+            Action::After { seconds, .. } if !valid_delay(*seconds) => {
+                bad_actions.push(format!("After in {} waits {} seconds", context, seconds));
+            }
+            Action::Repeat { times, interval, .. } => {
+                if *times == 0 {
+                    idle_repeats.push(format!("Repeat in {} has times: 0, so it never runs", context));
+                } else if *times > 1 && !valid_delay(*interval) {
+                    bad_actions.push(format!("Repeat in {} has interval {}", context, interval));
+                } else if *times > 1 && *interval == 0.0 {
+                    idle_repeats.push(format!("Repeat in {} has interval 0, so every run happens in the same frame", context));
+                }
+            }
+            _ => {}
+        }
+    });
+    for message in bad_actions {
+        linter.error(file, message);
+    }
+    for message in idle_repeats {
+        linter.warning(file, message);
+    }
+    for (context, tag) in tags_used {
+        if !tags_spawned.contains(&tag) {
+            linter.warning(file, format!("{} looks for tag '{}', but nothing in this level spawns it", context, tag));
+        }
+    }
+
+    lint_on_enter(linter, file, level);
+    for door in &level.doors {
+        lint_hints(linter, file, &format!("door '{}'", door.label), &door.hints);
+    }
+
+    lint_gotos(linter, file, "dialogue", &level.dialogue);
+    for (context, lines) in &queued_dialogues {
+        lint_gotos(linter, file, &format!("queued dialogue in {}", context), lines);
+    }
+
+    let mut flags_read = Vec::new();
+    for line in all_dialogue_lines(level) {
+        condition_flags(&line.condition, &mut flags_read);
+        for choice in &line.choices {
+            condition_flags(&choice.condition, &mut flags_read);
+        }
+    }
+    for door in &level.doors {
+        for conditional in &door.leads_to_if {
+            condition_flags(&conditional.condition, &mut flags_read);
+        }
+        for hint in &door.hints {
+            condition_flags(&hint.condition, &mut flags_read);
+        }
+    }
+    for reaction in &level.reactions {
+        trigger_flags(&reaction.trigger, &mut flags_read);
+    }
+    flags_read.sort();
+    flags_read.dedup();
+    for flag in flags_read {
+        if !flags_set.contains(&flag) {
+            linter.warning(file, format!("flag '{}' is read but nothing ever sets it", flag));
+        }
+    }
+}
+
+/// Times the game can turn into a `Duration` (it treats the rest as 0)
+fn valid_delay(seconds: f32) -> bool {
+    std::time::Duration::try_from_secs_f32(seconds).is_ok()
+}
+
+fn lint_map(linter: &mut Linter, file: &str, level: &LevelData) {
+    let Some(text) = &level.map else {
+        if level.room_type == "map" {
+            linter.error(file, "room_type is 'map' but there is no map".to_string());
+        }
+        return;
+    };
+    if level.room_type != "map" {
+        linter.warning(file, format!("has a map, but room_type '{}' doesn't use it", level.room_type));
+        return;
+    }
+
+    let map = TileMap::parse(text);
+    let doors = map.find(DOOR).len();
+    if doors != level.doors.len() {
+        linter.error(file, format!("map has {} door tile(s) but the level has {} door(s)", doors, level.doors.len()));
+    }
+    match map.find(PLAYER).len() {
+        0 => linter.warning(file, "map has no @, so the player starts at player_start".to_string()),
+        1 => {}
+        n => linter.warning(file, format!("map has {} @ tiles; the player starts on the first", n)),
+    }
+    let mut unknown: Vec<char> = map.tiles().map(|(_, _, tile)| tile).filter(|tile| !MAP_TILES.contains(tile)).collect();
+    unknown.sort();
+    unknown.dedup();
+    if !unknown.is_empty() {
+        let tiles: Vec<String> = unknown.iter().map(|tile| format!("'{}'", tile)).collect();
+        linter.warning(file, format!("map has unknown tile(s) {}, drawn as floor", tiles.join(", ")));
+    }
+}
+
+fn lint_cave(linter: &mut Linter, file: &str, level: &LevelData) {
+    let Some(cave) = &level.cave else { return };
+    if level.room_type != "cave" {
+        linter.warning(file, format!("has cave settings, but room_type '{}' doesn't use them", level.room_type));
+        return;
+    }
+    if cave.size.0 < MIN_CAVE_SIZE || cave.size.1 < MIN_CAVE_SIZE {
+        linter.warning(file, format!(
+            "cave size {:?} is below the {}x{} minimum it gets generated at",
+            cave.size, MIN_CAVE_SIZE, MIN_CAVE_SIZE
+        ));
+    }
+}
+
+fn lint_run(linter: &mut Linter, file: &str, run: &RunData, file_ids: &HashSet<&str>) {
+    let mut levels = vec![("start".to_string(), &run.start)];
+    for pool in &run.pools {
+        levels.extend(pool.levels.iter().map(|level| (format!("pool '{}'", pool.label), level)));
+        if pool.levels.is_empty() {
+            linter.warning(file, format!("pool '{}' has no levels, so it never comes up", pool.label));
+        }
+        lint_hints(linter, file, &format!("pool '{}'", pool.label), &pool.hints);
+    }
+    levels.extend(run.boss.iter().map(|level| ("boss".to_string(), level)));
+    for (context, level) in levels {
+        if !file_ids.contains(level.as_str()) {
+            linter.error(file, format!("{} uses '{}', which has no level file", context, level));
+        }
+    }
+
+    if run.doors == 0 {
+        linter.warning(file, "doors is 0; rooms offer one door anyway".to_string());
+    }
+    if run.boss.is_empty() {
+        linter.warning(file, "has no boss, so the run ends in a room with the doors from its file".to_string());
+    }
+    if let Some(depth) = (1..=run.length).find(|depth| run.pools.iter().all(|pool| pool.weight_at(*depth) <= 0.0)) {
+        linter.error(file, format!("no pool can come up at depth {}, so the run skips from there to the boss", depth));
+    }
+}
+
+// goto labels must name a line in the same dialogue
+fn lint_gotos(linter: &mut Linter, file: &str, context: &str, lines: &[DialogueLine]) {
+    let labels: HashSet<&str> = lines.iter().filter_map(|line| line.id.as_deref()).collect();
+    let gotos = lines.iter().flat_map(|line| {
+        line.goto.iter().chain(line.choices.iter().filter_map(|choice| choice.goto.as_ref()))
+    });
+    for goto in gotos {
+        if goto != END_LABEL && !labels.contains(goto.as_str()) {
+            linter.error(file, format!("{} has goto '{}' but no line with that id", context, goto));
+        }
+    }
+}
+
+// The fight runs a negative or non-finite timing as 0 rather than crash, so flag them here
+fn lint_boss(linter: &mut Linter, file: &str, boss: &BossData) {
+    let size = boss.arena.size;
+    if !size.0.is_finite() || !size.1.is_finite() || size.0 <= 0.0 || size.1 <= 0.0 {
+        linter.error(file, format!("arena has size {:?}", size));
+    }
+    for (number, phase) in (1..).zip(&boss.phases) {
+        if !phase.interval.is_finite() || phase.interval < 0.0 {
+            linter.error(file, format!("phase {} has interval {}", number, phase.interval));
+        }
+        for (step, pattern) in (1..).zip(&phase.patterns) {
+            if let Some(wait) = pattern.wait.filter(|wait| !wait.is_finite() || *wait < 0.0) {
+                linter.error(file, format!("phase {} pattern {} has wait {}", number, step, wait));
+            }
+        }
+    }
+}
+
+fn lint_on_enter(linter: &mut Linter, file: &str, level: &LevelData) {
+    for effect in &level.on_enter {
+        match effect {
+            RoomEffect::Heal(amount) | RoomEffect::Damage(amount) if *amount < 0 => {
+                linter.warning(file, format!("on_enter {:?} is negative and does nothing", effect));
+            }
+            RoomEffect::SpeedMultiplier(multiplier) if *multiplier <= 0.0 => {
+                linter.error(file, format!("on_enter SpeedMultiplier({}) would stop the player for good", multiplier));
+            }
+            RoomEffect::Buff { name, rooms, effect } | RoomEffect::Curse { name, rooms, effect } => {
+                if *rooms == 0 {
+                    linter.warning(file, format!("on_enter '{}' lasts 0 rooms, so it never starts", name));
+                }
+                match effect {
+                    LastingEffect::Speed(multiplier) if *multiplier <= 0.0 => {
+                        linter.error(file, format!("on_enter '{}' has Speed({}), which stops the player", name, multiplier));
+                    }
+                    LastingEffect::DamagePerRoom(amount) | LastingEffect::HealPerRoom(amount) if *amount < 0 => {
+                        linter.warning(file, format!("on_enter '{}' has a negative {:?} and does nothing", name, effect));
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn lint_hints(linter: &mut Linter, file: &str, context: &str, hints: &[DoorHint]) {
+    for (i, hint) in hints.iter().enumerate() {
+        if hint.icon.is_none() && hint.text.is_none() && hint.tint.is_none() {
+            linter.warning(file, format!("{} hint {} has no icon, text or tint, so it shows nothing", context, i + 1));
+        }
+        if matches!(hint.condition, Condition::Always) && i + 1 < hints.len() {
+            linter.warning(file, format!("{} hint {} always matches, so the hints after it never show", context, i + 1));
+        }
+    }
+}
+
+/// Flags something can set: SetFlag in any level, plus the ones the game sets itself
+fn flags_set(levels: &[LevelFile]) -> HashSet<String> {
+    let mut flags: HashSet<String> = HashSet::new();
+    flags.insert("boss_phase".to_string());
+    flags.insert(RUN_DEPTH_FLAG.to_string());
+
+    // Item pickups that count things (e.g. coins)
+    for (_, definition) in ItemRegistry::default().iter() {
+        for effect in &definition.effects {
+            if let ItemEffect::AddToFlag(key, _) | ItemEffect::SetFlag(key, _) = effect {
+                flags.insert(key.clone());
+            }
+        }
+    }
+
+    for level in levels {
+        let Ok(data) = &level.data else { continue };
+        // NPC health and fate are tracked per name (projectile.rs)
+        for npc in &data.npcs {
+            let name = npc.name.to_lowercase();
+            for suffix in ["health", "max_health", "present", "status"] {
+                flags.insert(format!("{}_{}", name, suffix));
+            }
+        }
+        for_each_action(data, &mut |action, _| {
+            if let Action::SetFlag { key, .. } = action {
+                flags.insert(key.clone());
+            }
+        });
+        for effect in &data.on_enter {
+            if let RoomEffect::SetFlag { key, .. } = effect {
+                flags.insert(key.clone());
+            }
+        }
+    }
+    flags
+}
+
+fn lint_reachability(linter: &mut Linter, levels: &[LevelFile], runs: &[RunFile]) {
+    let links: HashMap<&str, Vec<String>> = levels
+        .iter()
+        .filter_map(|level| {
+            let data = level.data.as_ref().ok()?;
+            Some((level.file_id.as_str(), level_links(data).into_iter().map(|link| link.to).collect()))
+        })
+        .collect();
+
+    if !links.contains_key(START_LEVEL) {
+        linter.error(&format!("levels/{}.ron", START_LEVEL), "start level is missing or doesn't parse".to_string());
+        return;
+    }
+
+    // Runs are started from the title menu, so their rooms count as reached too
+    let mut roots = vec![START_LEVEL];
+    for run in runs.iter().filter_map(|run| run.data.as_ref().ok()) {
+        let run_levels = run.pools.iter().flat_map(|pool| &pool.levels).chain(&run.boss).chain([&run.start]);
+        roots.extend(run_levels.filter_map(|level| links.get_key_value(level.as_str()).map(|(id, _)| *id)));
+    }
+    let mut reached: HashSet<&str> = roots.iter().copied().collect();
+    let mut queue: VecDeque<&str> = roots.into_iter().collect();
+    while let Some(level) = queue.pop_front() {
+        for to in links.get(level).into_iter().flatten() {
+            if let Some((id, _)) = links.get_key_value(to.as_str())
+                && reached.insert(id)
+            {
+                queue.push_back(id);
+            }
+        }
+    }
+
+    for level in levels {
+        if level.data.is_ok() && !reached.contains(level.file_id.as_str()) {
+            linter.warning(
+                &format!("levels/{}.ron", level.file_id),
+                format!("can't be reached from {}", START_LEVEL),
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use bevy::asset::AssetLoadFailedEvent;
+use crate::cave::roll_cave_seed;
+use crate::item::{CollectedItems, ItemRegistry};
+use crate::level::{CurrentLevel, LoadedLevelData, spawn_level_from_data_internal};
+use crate::level_entity::LevelEntity;
+use crate::level_schema::LevelData;
+use crate::player::Player;
+use crate::reaction::ScheduledActions;
+use crate::run::{CurrentRun, enter_run_room};
+use crate::state::GameState;
+use crate::state::boss_fight::{BossFightInitialized, respawn_boss_arena};
+use crate::story_flags::StoryFlags;
+
+/// Respawns the current room when its level file changes on disk (`--features dev` only)
+pub struct LevelReloadPlugin;
+
+impl Plugin for LevelReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HotReloadSettings>()
+           .init_resource::<RoomEntryFlags>()
+           .add_systems(OnExit(GameState::LoadingNewLevel), snapshot_room_entry_flags)
+           .add_systems(Update, (report_failed_reload, hot_reload_level).run_if(
+               in_state(GameState::Playing)
+                   .or(in_state(GameState::Dialogue))
+                   .or(in_state(GameState::BossFight))
+                   .or(in_state(GameState::Paused))
+           ));
+    }
+}
+
+/// What survives a hot reload
+#[derive(Resource)]
+pub struct HotReloadSettings {
+    /// Put the player back where they were instead of at `player_start`
+    pub keep_player_position: bool,
+    /// Keep flags set since entering the room; when false they go back to how they were on entry
+    pub keep_story_flags: bool,
+}
+
+impl Default for HotReloadSettings {
+    fn default() -> Self {
+        Self { keep_player_position: true, keep_story_flags: true }
+    }
+}
+
+/// Story flags as they were when the current room finished loading
+#[derive(Resource, Default)]
+struct RoomEntryFlags(StoryFlags);
+
+fn snapshot_room_entry_flags(story_flags: Res<StoryFlags>, mut entry_flags: ResMut<RoomEntryFlags>) {
+    entry_flags.0 = story_flags.clone();
+}
+
+// A file that no longer parses leaves the old room in place
+fn report_failed_reload(
+    mut failed: MessageReader<AssetLoadFailedEvent<LevelData>>,
+    current_level: Res<CurrentLevel>,
+) {
+    for event in failed.read() {
+        if event.id == current_level.handle.id() {
+            warn!("Hot reload of {} failed, keeping the old room: {}", event.path, event.error);
+        }
+    }
+}
+
+fn hot_reload_level(
+    mut commands: Commands,
+    mut asset_events: MessageReader<AssetEvent<LevelData>>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<LevelData>>,
+    mut loaded_data: ResMut<LoadedLevelData>,
+    settings: Res<HotReloadSettings>,
+    entry_flags: Res<RoomEntryFlags>,
+    mut story_flags: ResMut<StoryFlags>,
+    // Grouped to stay under Bevy's limit on system parameters
+    (item_registry, collected_items): (Res<ItemRegistry>, Res<CollectedItems>),
+    windows: Query<&Window>,
+    level_entities: Query<Entity, With<LevelEntity>>,
+    player_query: Query<&Transform, With<Player>>,
+    boss_initialized: Res<BossFightInitialized>,
+    mut scheduled: ResMut<ScheduledActions>,
+    mut current_run: ResMut<CurrentRun>,
+) {
+    let modified = asset_events.read().any(|event| event.is_modified(&current_level.handle));
+    if !modified || !current_level.loaded {
+        return;
+    }
+    let Some(level_data) = level_assets.get(&current_level.handle) else { return };
+    info!("Level file changed, reloading {}", level_data.name);
+
+    let player_transform = player_query.single().ok().copied();
+    if !settings.keep_story_flags {
+        *story_flags = entry_flags.0.clone();
+    }
+
+    let mut spawn_data = level_data.clone();
+    // Run rooms get their doors from the run again
+    enter_run_room(&mut current_run, &current_level.level_id, &mut spawn_data, &mut story_flags);
+    // A cave without a fixed seed keeps the layout it was given on entry
+    let entry_seed = loaded_data.0.as_ref().and_then(|data| data.cave.as_ref()).and_then(|cave| cave.seed);
+    if spawn_data.cave.as_ref().is_none_or(|cave| cave.seed.is_none()) && entry_seed.is_some() {
+        spawn_data.cave.get_or_insert_with(Default::default).seed = entry_seed;
+    }
+    roll_cave_seed(&mut spawn_data);
+
+    // Pending Wait/After/Repeat actions belong to the old version of the room
+    scheduled.clear();
+    for entity in &level_entities {
+        commands.entity(entity).despawn();
+    }
+    loaded_data.0 = Some(spawn_data.clone());
+    let player = spawn_level_from_data_internal(
+        &mut commands,
+        &spawn_data,
+        &current_level.level_id,
+        &windows,
+        &story_flags,
+        &item_registry,
+        &collected_items,
+    );
+    if settings.keep_player_position
+        && let Some(transform) = player_transform
+    {
+        commands.entity(player).insert(transform);
+    }
+
+    // The arena and boss went with the room. Once the fight has started, put them back but
+    // keep its phase and attack timer (a dialogue mid-fight doesn't take the arena down either)
+    if boss_initialized.0 {
+        commands.run_system_cached(respawn_boss_arena);
+    }
+}
@@ -2,13 +2,26 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use crate::story_flags::FlagValue;
 
+/// `goto` target that finishes the dialogue
+pub const END_LABEL: &str = "END";
+
 #[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
 pub struct LevelData {
     pub id: String,
     pub name: String,
     pub room_type: String,
+    /// Where the player appears (a `map` room's `@` tile takes over)
+    #[serde(default)]
     pub player_start: (f32, f32),
 
+    /// Layout of a `map` room, drawn in ASCII (see `tile_map.rs`)
+    #[serde(default)]
+    pub map: Option<String>,
+
+    /// Seed, generator and size of a `cave` room (defaults when None)
+    #[serde(default)]
+    pub cave: Option<CaveSettings>,
+
     #[serde(default)]
     pub dialogue: Vec<DialogueLine>,
 
@@ -28,24 +41,161 @@ pub struct LevelData {
 
     #[serde(default)]
     pub reactions: Vec<Reaction>,
+
+    #[serde(default)]
+    pub zones: Vec<TriggerZoneData>,
+
+    /// Applied once the room finishes loading (see `room_effect.rs`)
+    #[serde(default)]
+    pub on_enter: Vec<RoomEffect>,
+}
+
+/// How a `cave` room is generated (see `cave.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaveSettings {
+    /// Same seed, same cave. None rolls a new seed each time the room loads.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub generator: CaveGenerator,
+    /// Width and height in tiles
+    #[serde(default = "default_cave_size")]
+    pub size: (usize, usize),
+}
+
+impl Default for CaveSettings {
+    fn default() -> Self {
+        Self { seed: None, generator: CaveGenerator::default(), size: default_cave_size() }
+    }
+}
+
+fn default_cave_size() -> (usize, usize) {
+    (50, 50)
+}
+
+/// `mapgen` filters a cave can be built with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaveGenerator {
+    /// Noise smoothed into open caverns
+    #[default]
+    CellularAutomata,
+    /// Rectangular rooms joined by corridors
+    BspRooms,
+    /// Rooms packed wall to wall
+    BspInterior,
+    /// Randomly placed rooms joined by corridors
+    SimpleRooms,
+    /// Winding tunnels dug by random walkers
+    DrunkardsWalk,
+    Maze,
+    /// Cells around random seed points
+    Voronoi,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DialogueLine {
     pub speaker: String,
     pub text: String,
+    /// Line is skipped unless this holds
+    #[serde(default)]
+    pub condition: Condition,
+    /// Label other lines and choices can jump to with `goto`
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Player picks one of these (2-4) before the dialogue continues
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Jump to the line with this id after this one ("END" finishes the dialogue)
+    #[serde(default)]
+    pub goto: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    /// Choice is hidden unless this holds
+    #[serde(default)]
+    pub condition: Condition,
+    /// Run when the choice is picked (e.g. SetFlag, GiveKey)
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    /// Line id to continue from, otherwise the next line ("END" finishes the dialogue)
+    #[serde(default)]
+    pub goto: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DoorData {
+    /// Ignored in `map` rooms, where the door goes on its `D` tile
+    #[serde(default)]
     pub position: (f32, f32),
     pub leads_to: String,
+    /// Checked in order when the door is touched; first match wins, otherwise `leads_to`
+    #[serde(default)]
+    pub leads_to_if: Vec<ConditionalDestination>,
     pub label: String,
     pub locked: bool,
+    /// Key needed to open a locked door. `None` means any generic "key" works.
     #[serde(default)]
     pub key_required: Option<String>,
+    /// Whether the key is used up when the door opens (default: true)
+    #[serde(default = "default_consume_key")]
+    pub consume_key: bool,
     #[serde(default)]
     pub extra: Vec<EntityComponent>,
+    /// Show `label` under the door while the player is near it
+    #[serde(default)]
+    pub show_label: bool,
+    /// What the door gives away while the player is near it. Checked in order, first match wins.
+    #[serde(default)]
+    pub hints: Vec<DoorHint>,
+}
+
+/// A preview of what's behind a door, e.g.
+/// `(condition: Has("lantern"), icon: Some("!"), text: Some("Gears grind beyond."), tint: Some((1.0, 0.4, 0.3)))`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DoorHint {
+    #[serde(default)]
+    pub condition: Condition,
+    /// Glyph shown above the label and text
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Whispered line under the label
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Colour of the door (RGB, 0 to 1)
+    #[serde(default)]
+    pub tint: Option<(f32, f32, f32)>,
+}
+
+fn default_consume_key() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalDestination {
+    pub condition: Condition,
+    pub level: String,
+}
+
+/// A check against `StoryFlags`, e.g. `Equals("duck_status", Text("alive"))`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    #[default]
+    Always,
+    Equals(String, FlagValue),
+    NotEquals(String, FlagValue),
+    /// Flag is a Number greater than the given value
+    GreaterThan(String, i32),
+    /// Flag is a Number at least the given value
+    AtLeast(String, i32),
+    /// Flag is a Number less than the given value
+    LessThan(String, i32),
+    /// Flag is set (and isn't `Bool(false)`)
+    Has(String),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
 }
 
 /// Extra components that can be attached to entities via RON
@@ -59,6 +209,9 @@ pub enum EntityComponent {
 pub struct ItemData {
     pub item_type: String,
     pub position: (f32, f32),
+    /// Keys use this as the key name (e.g. "vault_key"), otherwise a generic "key"
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,20 +222,88 @@ pub struct NpcData {
     pub extra: Vec<EntityComponent>,
 }
 
+/// Invisible box that sends `ZoneEntered`/`ZoneLeft` events carrying its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerZoneData {
+    pub name: String,
+    /// Centre of the box
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    /// Only the first entry counts; the zone is gone until the room is entered again
+    #[serde(default)]
+    pub once: bool,
+}
+
+/// What a room does to the player on the way in, e.g. `Curse(name: "Heavy Legs", rooms: 3, effect: Speed(0.6))`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RoomEffect {
+    Heal(i8),
+    Damage(i8),
+    /// Raise (or lower) max health; raising it also fills the new hearts
+    MaxHealth(i8),
+    /// Multiplies the player's speed for good, like the speed boost item
+    SpeedMultiplier(f32),
+    GrantKey(String),
+    SetFlag { key: String, value: FlagValue },
+    /// A named effect for this room and the `rooms - 1` after it
+    Buff { name: String, rooms: u32, effect: LastingEffect },
+    /// Same as `Buff`, shown as a curse
+    Curse { name: String, rooms: u32, effect: LastingEffect },
+}
+
+/// What a buff or curse does while it lasts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LastingEffect {
+    /// Player speed is multiplied by this
+    Speed(f32),
+    /// Hit points lost on entering each room
+    DamagePerRoom(i8),
+    /// Hit points gained on entering each room
+    HealPerRoom(i8),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Reaction {
     pub trigger: Trigger,
     pub actions: Vec<Action>,
+    /// Fire only the first time the trigger matches this run
+    #[serde(default)]
+    pub once: bool,
+    /// Only the highest priority match runs (ties go to the first in the file)
+    #[serde(default)]
+    pub priority: i32,
+    /// After running, let the next matching reaction run too
+    #[serde(default)]
+    pub fallthrough: bool,
 }
 
+/// When a reaction fires. Checked each time a `LevelEvent` is sent, e.g.
+/// `All([Event("ProjectilesDone"), Flag(AtLeast("boss_phase", 2)), Not(HasKey("key"))])`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Trigger {
     Event(String),
+    /// An event carrying this door label, item type or NPC name, e.g. `EventWith(event: "NpcDied", value: "duck")`
+    EventWith {
+        event: String,
+        value: String,
+    },
     EventAndFlag {
         event: String,
         flag: String,
         equals: FlagValue,
     },
+    /// Story flag check, same as a dialogue line's `condition`
+    Flag(Condition),
+    /// Player has fewer hit points than this
+    HealthBelow(i8),
+    HealthAtLeast(i8),
+    /// Player is carrying at least one of this key
+    HasKey(String),
+    /// An NPC with this name is in the room
+    NpcPresent(String),
+    All(Vec<Trigger>),
+    Any(Vec<Trigger>),
+    Not(Box<Trigger>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,4 +315,38 @@ pub enum Action {
     RestartProjectiles { count: u32 },
     TransitionToLevel { level_id: String },
     SetNextLevel { level_id: String },  // Sets level_id without transitioning (use with QueueDialogue then: "LoadingNewLevel")
+    GiveKey { name: String },  // Adds a key to the player's inventory ("key" opens any locked door without key_required)
+    /// Hold the rest of this action list for a while (the clock only runs in Playing and BossFight)
+    Wait { seconds: f32 },
+    /// Run `actions` later, carrying on with the rest of the list straight away
+    After { seconds: f32, actions: Vec<Action> },
+    /// Run `actions` now and then every `interval` seconds, `times` runs in all
+    Repeat { times: u32, interval: f32, actions: Vec<Action> },
+    /// Spawn an NPC, item, door or wall segment, built the same way as the level file's own
+    SpawnEntity {
+        entity: EntityData,
+        /// Name for `DespawnTagged`/`ModifyTagged` to find it by
+        #[serde(default)]
+        tag: Option<String>,
+    },
+    /// Remove everything with this tag (boss arenas are tagged "arena")
+    DespawnTagged { tag: String },
+    /// Move everything with this tag and/or give it extra components
+    ModifyTagged {
+        tag: String,
+        #[serde(default)]
+        move_to: Option<(f32, f32)>,
+        #[serde(default)]
+        extra: Vec<EntityComponent>,
+    },
+}
+
+/// Something `SpawnEntity` can put in the room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EntityData {
+    Npc(NpcData),
+    Item(ItemData),
+    Door(DoorData),
+    /// A straight line of wall tiles from one point to the other
+    Wall { from: (f32, f32), to: (f32, f32) },
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+pub mod art;
+pub mod helpers;
+pub mod player;
+pub mod door;
+pub mod roaming;
+pub mod state;
+pub mod hitbox;
+pub mod wall;
+pub mod level;
+pub mod level_entity;
+pub mod level_schema;
+pub mod level_index;
+pub mod level_lint;
+pub mod level_graph;
+#[cfg(feature = "dev")]
+pub mod level_reload;
+pub mod boss_schema;
+pub mod run_schema;
+pub mod run;
+pub mod room_effect;
+pub mod bullet_pattern;
+pub mod camera;
+pub mod projectile;
+pub mod ui;
+pub mod npc;
+pub mod follow;
+pub mod story_flags;
+pub mod inventory;
+pub mod item;
+pub mod trigger_zone;
+pub mod tile_map;
+pub mod cave;
+pub mod save;
+pub mod audio;
+pub mod level_event;
+pub mod reaction;
+#[cfg(feature = "dev")]
+pub mod dev_console;
+use crate::audio::CurrentMusic;
+use crate::camera::CameraPlugin;
+use crate::projectile::ProjectilePlugin;
+use crate::door::DoorPlugin;
+use crate::player::PlayerPlugin;
+use crate::roaming::RoamingPlugin;
+use crate::hitbox::HitBoxPlugin;
+use crate::level::LevelPlugin;
+use crate::follow::FollowPlugin;
+use crate::item::ItemPlugin;
+use crate::trigger_zone::TriggerZonePlugin;
+use crate::run::RunPlugin;
+use crate::room_effect::RoomEffectPlugin;
+
+/// All gameplay plugins. Expects windowing, assets, input and states to be set up already
+/// (DefaultPlugins in the game, a headless setup in tests).
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentMusic>()
+           .add_plugins(state::StatePlugin)
+           .add_plugins(CameraPlugin)
+           .add_plugins(HitBoxPlugin)
+           .add_plugins(LevelPlugin)
+           .add_plugins(PlayerPlugin)
+           .add_plugins(DoorPlugin)
+           .add_plugins(RoamingPlugin)
+           .add_plugins(ProjectilePlugin)
+           .add_plugins(FollowPlugin)
+           .add_plugins(ItemPlugin)
+           .add_plugins(TriggerZonePlugin)
+           .add_plugins(RunPlugin)
+           .add_plugins(RoomEffectPlugin);
+
+        #[cfg(feature = "dev")]
+        app.add_plugins(dev_console::DevConsolePlugin);
+    }
+}
@@ -1,33 +1,5 @@
 use bevy::prelude::*;
-mod art;
-mod helpers;
-mod player;
-mod door;
-mod roaming;
-mod state;
-mod hitbox;
-mod wall;
-mod level;
-mod level_entity;
-mod level_schema;
-mod camera;
-mod projectile;
-mod ui;
-mod npc;
-mod follow;
-mod story_flags;
-mod audio;
-mod level_event;
-mod reaction;
-use crate::audio::CurrentMusic;
-use crate::camera::CameraPlugin;
-use crate::projectile::ProjectilePlugin;
-use crate::door::DoorPlugin;
-use crate::player::PlayerPlugin;
-use crate::roaming::RoamingPlugin;
-use crate::hitbox::HitBoxPlugin;
-use crate::level::LevelPlugin;
-use crate::follow::FollowPlugin;
+use bevy_game::GamePlugin;
 
 fn main() {
     App::new()
@@ -38,15 +10,6 @@ fn main() {
         }),
         ..default()
     }))
-    .init_resource::<CurrentMusic>()
-    .add_plugins(state::StatePlugin)
-    .add_plugins(CameraPlugin)
-    .add_plugins(HitBoxPlugin)
-    .add_plugins(LevelPlugin)
-    .add_plugins(PlayerPlugin)
-    .add_plugins(DoorPlugin)
-    .add_plugins(RoamingPlugin)
-    .add_plugins(ProjectilePlugin)
-    .add_plugins(FollowPlugin)
+    .add_plugins(GamePlugin)
     .run();
 }
@@ -1,20 +1,51 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::state::GameState;
 use crate::hitbox::HitBox;
 use crate::wall::Wall;
+use crate::room_effect::ActiveEffects;
 
-#[derive(Resource)]
+/// Max health a new game or restart begins with; `MaxHealth` room effects change it from there
+pub const BASE_MAX_HEALTH: i8 = 3;
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct PlayerHealth{
     pub current: i8,
     pub max: i8
 }
 
+impl Default for PlayerHealth {
+    fn default() -> Self {
+        Self { current: BASE_MAX_HEALTH, max: BASE_MAX_HEALTH }
+    }
+}
+
+/// Run-long bonuses picked up from items
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct PlayerModifiers {
+    pub speed_multiplier: f32,
+    pub shield: u8, // Hits absorbed before health is lost
+}
+
+impl Default for PlayerModifiers {
+    fn default() -> Self {
+        Self { speed_multiplier: 1.0, shield: 0 }
+    }
+}
+
+/// Player takes no damage (dev console `god`)
+#[derive(Resource, Default)]
+pub struct GodMode(pub bool);
+
 #[derive(Component)]
 pub struct Player;
 
+#[allow(clippy::type_complexity)]
 fn move_player(
     input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
+    modifiers: Res<PlayerModifiers>,
+    effects: Res<ActiveEffects>,
     mut player_query: Single<(&mut Transform, &HitBox), With<Player>>,
     walls: Query<(&Transform, &HitBox), (With<Wall>, Without<Player>)>,
 ){
@@ -35,7 +66,7 @@ fn move_player(
     }
 
     if direction != Vec2::ZERO{
-        let speed = 300.0;
+        let speed = 300.0 * modifiers.speed_multiplier * effects.speed_multiplier();
         let delta = direction.normalize() * speed * time.delta_secs();
         let desired_pos = Vec2::new(
             player_transform.translation.x + delta.x,
@@ -67,7 +98,9 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         // Player is spawned by level.rs from RON data (player_start)
-        app.add_systems(Update, move_player.run_if(
+        app.init_resource::<PlayerModifiers>()
+           .init_resource::<GodMode>()
+           .add_systems(Update, move_player.run_if(
             in_state(GameState::Playing).or(in_state(GameState::BossFight))
         ));
     }
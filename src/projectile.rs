@@ -1,35 +1,116 @@
 use bevy::prelude::*;
+use std::f32::consts::TAU;
 use crate::hitbox::HitBox;
 use crate::audio::play_sfx;
 use crate::level_entity::LevelEntity;
-use crate::player::PlayerHealth;
+use crate::level_event::LevelEvent;
+use crate::player::{GodMode, Player, PlayerHealth, PlayerModifiers};
 use crate::state::GameState;
 use crate::hitbox::PlayerTouchedSomething;
 use crate::npc::Npc;
 use crate::story_flags::{StoryFlags, FlagValue};
+use crate::wall::Wall;
+use crate::camera::GameCamera;
 
+/// Seconds a projectile lives unless the spawner says otherwise
+pub const DEFAULT_LIFETIME: f32 = 10.0;
+/// How far past the edge of the screen a projectile can go before it's retired
+const CULL_MARGIN: f32 = 100.0;
+/// Retired projectiles kept around for reuse; any beyond this are despawned
+const MAX_POOLED: usize = 256;
 
 #[derive(Component)]
 pub struct Projectile {
     pub velocity: Vec2,
+    /// Seconds left before it's retired
+    pub lifetime: f32,
+    /// Retire on touching a `Wall` (off by default so shots can cross the arena edge)
+    pub despawn_on_wall: bool,
 }
 
+impl Projectile {
+    pub fn new(velocity: Vec2) -> Self {
+        Self {
+            velocity,
+            lifetime: DEFAULT_LIFETIME,
+            despawn_on_wall: false,
+        }
+    }
+}
+
+/// Hidden projectile entities waiting to be reused by `spawn_projectile_at`
+#[derive(Resource, Default)]
+pub struct ProjectilePool {
+    free: Vec<Entity>,
+    /// Retired this frame; their strip commands may not have applied yet
+    retired: Vec<Entity>,
+}
+
+/// Take a projectile out of play. It's stripped and hidden rather than despawned so the next
+/// shot can reuse the entity. Pooled entities drop `LevelEntity` so level changes leave them be.
+pub fn retire_projectile(commands: &mut Commands, pool: &mut ProjectilePool, entity: Entity) {
+    // Already retired this frame (e.g. hit the player and a wall at once)
+    if pool.retired.contains(&entity) {
+        return;
+    }
+    if pool.free.len() + pool.retired.len() >= MAX_POOLED {
+        commands.entity(entity).despawn();
+        return;
+    }
+    commands.entity(entity)
+        .remove::<(Projectile, HitBox, Wave, Homing, LevelEntity)>()
+        .insert(Visibility::Hidden);
+    pool.retired.push(entity);
+}
+
+// Last frame's retirees are fully stripped now, so they can be handed out again
+fn recycle_retired_projectiles(mut pool: ResMut<ProjectilePool>) {
+    let ProjectilePool { free, retired } = &mut *pool;
+    free.append(retired);
+}
+
+/// Weaves side to side across its direction of travel
+#[derive(Component)]
+pub struct Wave {
+    pub amplitude: f32,
+    /// Oscillations per second
+    pub frequency: f32,
+    pub age: f32,
+}
+
+/// Turns toward the player, at most `turn_rate` radians per second
+#[derive(Component)]
+pub struct Homing {
+    pub turn_rate: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_projectile_touch_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut messages: MessageReader<PlayerTouchedSomething>,
     projectiles: Query<(), With<Projectile>>,
+    mut pool: ResMut<ProjectilePool>,
     mut health: ResMut<PlayerHealth>,
+    mut modifiers: ResMut<PlayerModifiers>,
+    god_mode: Res<GodMode>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     for message in messages.read() {
         // Try to get the LevelDoor component from the touched entity
         if projectiles.get(message.messaging_entity).is_ok() {
             info!("Projectile hit player!");
+            retire_projectile(&mut commands, &mut pool, message.messaging_entity);
+            if god_mode.0 {
+                continue;
+            }
+            if modifiers.shield > 0 {
+                modifiers.shield -= 1;
+                info!("Shield absorbed the hit ({} left)", modifiers.shield);
+                continue;
+            }
             //sound of projectile hit
             play_sfx(&mut commands, &asset_server, "player_hit", "mp3");
-
-            commands.entity(message.messaging_entity).despawn();
             health.current -= 1;// TODO: HARD CODED
             if health.current == 0{
                 next_state.set(GameState::Defeat)
@@ -40,11 +121,40 @@ fn handle_projectile_touch_player(
 
 fn move_projectiles(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &Projectile)>,
+    mut query: Query<(&mut Transform, &Projectile, Option<&mut Wave>)>,
 ) {
-    for (mut transform, projectile) in &mut query {
-        transform.translation.x += projectile.velocity.x * time.delta_secs();
-        transform.translation.y += projectile.velocity.y * time.delta_secs();
+    let dt = time.delta_secs();
+    for (mut transform, projectile, wave) in &mut query {
+        transform.translation.x += projectile.velocity.x * dt;
+        transform.translation.y += projectile.velocity.y * dt;
+
+        // Sideways offset follows amplitude * sin(2*pi*f*t), applied as its derivative
+        if let Some(mut wave) = wave {
+            let omega = TAU * wave.frequency;
+            let side = projectile.velocity.normalize_or_zero().perp();
+            let drift = side * wave.amplitude * omega * (omega * wave.age).cos() * dt;
+            transform.translation += drift.extend(0.0);
+            wave.age += dt;
+        }
+    }
+}
+
+fn steer_homing_projectiles(
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut query: Query<(&Transform, &mut Projectile, &Homing), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.single() else { return };
+    let player_pos = player_transform.translation.truncate();
+
+    for (transform, mut projectile, homing) in &mut query {
+        let to_player = player_pos - transform.translation.truncate();
+        let angle = projectile.velocity.angle_to(to_player);
+        if !angle.is_finite() {
+            continue;
+        }
+        let max_turn = homing.turn_rate * time.delta_secs();
+        projectile.velocity = Vec2::from_angle(angle.clamp(-max_turn, max_turn)).rotate(projectile.velocity);
     }
 }
 
@@ -54,11 +164,18 @@ fn handle_projectile_touch_npc(
     projectiles: Query<(Entity, &Transform, &HitBox), With<Projectile>>,
     npcs: Query<(Entity, &Transform, &HitBox, &Npc)>,
     mut story_flags: ResMut<StoryFlags>,
+    mut pool: ResMut<ProjectilePool>,
+    mut level_events: MessageWriter<LevelEvent>,
 ) {
+    // The despawn is deferred, so don't let a second projectile kill the same NPC this frame
+    let mut died = Vec::new();
     for (proj_entity, proj_transform, proj_hitbox) in &projectiles {
         let proj_pos = proj_transform.translation;
 
         for (npc_entity, npc_transform, npc_hitbox, npc) in &npcs {
+            if died.contains(&npc_entity) {
+                continue;
+            }
             let npc_pos = npc_transform.translation;
 
             // AABB overlap check
@@ -81,8 +198,7 @@ fn handle_projectile_touch_npc(
 
                 info!("Projectile hit NPC '{}', health: {} -> {}", npc.name, current_health, new_health);
 
-                // Despawn projectile
-                commands.entity(proj_entity).despawn();
+                retire_projectile(&mut commands, &mut pool, proj_entity);
 
                 if new_health <= 0 {
                     // NPC dies
@@ -91,6 +207,8 @@ fn handle_projectile_touch_npc(
                     story_flags.set(&status_key, FlagValue::Text("died_in_boss".to_string()));
                     story_flags.set(&health_key, FlagValue::Number(0));
                     commands.entity(npc_entity).despawn();
+                    died.push(npc_entity);
+                    level_events.write(LevelEvent::NpcDied { name: npc.name.clone() });
                 } else {
                     // Update health
                     story_flags.set(&health_key, FlagValue::Number(new_health));
@@ -102,29 +220,91 @@ fn handle_projectile_touch_npc(
     }
 }
 
+// Retire projectiles that outlived their lifetime, left the screen, or (if they opt in) hit a wall
+fn cull_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<ProjectilePool>,
+    mut projectiles: Query<(Entity, &Transform, &HitBox, &mut Projectile)>,
+    walls: Query<(&Transform, &HitBox), With<Wall>>,
+    camera_query: Query<&Transform, With<GameCamera>>,
+    windows: Query<&Window>,
+) {
+    let view = match (camera_query.single(), windows.single()) {
+        (Ok(camera), Ok(window)) => Some((
+            camera.translation.truncate(),
+            Vec2::new(window.width(), window.height()) / 2.0 + CULL_MARGIN,
+        )),
+        _ => None,
+    };
+
+    for (entity, transform, hitbox, mut projectile) in &mut projectiles {
+        let pos = transform.translation.truncate();
+
+        projectile.lifetime -= time.delta_secs();
+        if projectile.lifetime <= 0.0 {
+            retire_projectile(&mut commands, &mut pool, entity);
+            continue;
+        }
+
+        if let Some((center, half_size)) = view {
+            let offset = (pos - center).abs();
+            if offset.x > half_size.x || offset.y > half_size.y {
+                retire_projectile(&mut commands, &mut pool, entity);
+                continue;
+            }
+        }
+
+        if projectile.despawn_on_wall {
+            let hit_wall = walls.iter().any(|(wall_transform, wall_hitbox)| {
+                let wall_pos = wall_transform.translation.truncate();
+                (pos.x - wall_pos.x).abs() < (hitbox.width + wall_hitbox.width) / 2.0
+                    && (pos.y - wall_pos.y).abs() < (hitbox.height + wall_hitbox.height) / 2.0
+            });
+            if hit_wall {
+                retire_projectile(&mut commands, &mut pool, entity);
+            }
+        }
+    }
+}
+
+/// Put a projectile in play, reusing a pooled entity when there is one
 pub fn spawn_projectile_at(
     commands: &mut Commands,
+    pool: &mut ProjectilePool,
     pos: Vec3,
-    velocity: Vec2,
+    projectile: Projectile,
     shape: &str,
-) {
-    commands.spawn((
+) -> Entity {
+    let bundle = (
         Text2d::new(shape),
         TextFont { font_size: 24.0, ..default() },
         TextColor(Color::srgb(1.0, 0.3, 0.3)),
         Transform::from_translation(pos),
-        Projectile { velocity },
+        Visibility::Inherited,
+        projectile,
         LevelEntity,
         HitBox { width: 24.0, height: 24.0 },
-    ));
+    );
+    match pool.free.pop() {
+        Some(entity) => {
+            commands.entity(entity).insert(bundle);
+            entity
+        }
+        None => commands.spawn(bundle).id(),
+    }
 }
 
 pub struct ProjectilePlugin;
 
 impl Plugin for ProjectilePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (
+        app.init_resource::<ProjectilePool>()
+           .add_systems(First, recycle_retired_projectiles)
+           .add_systems(Update, (
+            steer_homing_projectiles.before(move_projectiles),
             move_projectiles,
+            cull_projectiles.after(move_projectiles),
             handle_projectile_touch_player,
             handle_projectile_touch_npc,
         ).run_if(in_state(GameState::BossFight)));
@@ -1,14 +1,18 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use std::collections::HashSet;
+use std::time::Duration;
 use crate::level::LoadedLevelData;
 use crate::level_event::LevelEvent;
-use crate::level_schema::{Action, DialogueLine, Trigger};
-use crate::state::boss_fight::{AttackTimer, PlayerArena};
+use crate::level_schema::{Action, DialogueLine, DoorData, EntityData, Reaction, Trigger};
+use crate::state::boss_fight::{ARENA_TAG, AttackTimer};
 use crate::story_flags::StoryFlags;
-use crate::wall::Wall;
-use crate::level_entity::LevelEntity;
-use crate::level::{CurrentLevel, LevelDoor};
-use crate::hitbox::HitBox;
-use crate::art::DOOR_ART;
+use crate::inventory::Inventory;
+use crate::item::{ItemRegistry, spawn_item_from_data};
+use crate::level_entity::Tagged;
+use crate::level::{CurrentLevel, insert_extra_components, spawn_door_from_data, spawn_npc_from_data, spawn_wall_segment};
+use crate::npc::Npc;
+use crate::player::PlayerHealth;
 use crate::state::GameState;
 
 /// Resource for queued mid-level dialogue
@@ -16,6 +20,8 @@ use crate::state::GameState;
 pub struct QueuedDialogue {
     pub lines: Vec<DialogueLine>,
     pub then_state: String,
+    /// Lines were queued while a dialogue was showing; the open panel starts over on them
+    pub restart: bool,
 }
 
 impl QueuedDialogue {
@@ -26,145 +32,341 @@ impl QueuedDialogue {
     pub fn clear(&mut self) {
         self.lines.clear();
         self.then_state.clear();
+        self.restart = false;
     }
 }
 
-fn trigger_matches(trigger: &Trigger, event: &LevelEvent, story_flags: &StoryFlags) -> bool {
+/// Reactions with `once: true` that have already fired, as `<level_id>/<index>`
+#[derive(Resource, Default)]
+pub struct FiredReactions(pub HashSet<String>);
+
+/// Actions waiting on a `Wait`, `After` or `Repeat`. Kept across Dialogue and BossFight so a
+/// sequence carries on after a queued dialogue; cleared when a new room loads.
+#[derive(Resource, Default)]
+pub struct ScheduledActions(Vec<ScheduledAction>);
+
+struct ScheduledAction {
+    timer: Timer,
+    actions: Vec<Action>,
+    /// Further runs after this one (`Repeat`)
+    repeats_left: u32,
+}
+
+/// A negative, NaN or too large `Wait`/`After`/`Repeat` time counts as 0 instead of
+/// panicking (doorgame-lint reports them)
+fn delay(seconds: f32) -> Duration {
+    Duration::try_from_secs_f32(seconds).unwrap_or(Duration::ZERO)
+}
+
+impl ScheduledActions {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn after(&mut self, seconds: f32, actions: Vec<Action>) {
+        self.0.push(ScheduledAction {
+            timer: Timer::new(delay(seconds), TimerMode::Once),
+            actions,
+            repeats_left: 0,
+        });
+    }
+
+    fn every(&mut self, interval: f32, times: u32, actions: Vec<Action>) {
+        self.0.push(ScheduledAction {
+            timer: Timer::new(delay(interval), TimerMode::Repeating),
+            actions,
+            repeats_left: times - 1,
+        });
+    }
+
+    /// Advance every timer, returning the action lists that are due, in order
+    fn tick(&mut self, delta: Duration) -> Vec<Vec<Action>> {
+        let mut due = Vec::new();
+        self.0.retain_mut(|scheduled| {
+            scheduled.timer.tick(delta);
+            if scheduled.timer.mode() == TimerMode::Once {
+                if scheduled.timer.is_finished() {
+                    due.push(std::mem::take(&mut scheduled.actions));
+                    return false;
+                }
+                return true;
+            }
+            for _ in 0..scheduled.timer.times_finished_this_tick().min(scheduled.repeats_left) {
+                due.push(scheduled.actions.clone());
+                scheduled.repeats_left -= 1;
+            }
+            scheduled.repeats_left > 0
+        });
+        due
+    }
+}
+
+/// What a trigger is checked against: the event, and the game as it was when the event arrived
+pub struct TriggerContext<'a> {
+    pub event: &'a LevelEvent,
+    pub story_flags: &'a StoryFlags,
+    pub health: &'a PlayerHealth,
+    pub inventory: &'a Inventory,
+    /// Names of NPCs in the room
+    pub npcs: &'a [String],
+}
+
+pub fn trigger_matches(trigger: &Trigger, context: &TriggerContext) -> bool {
     match trigger {
         Trigger::Event(event_name) => {
-            event_matches_name(event, event_name)
+            event_matches_name(context.event, event_name)
+        }
+        Trigger::EventWith { event: event_name, value } => {
+            event_matches_name(context.event, event_name) && context.event.value() == Some(value.as_str())
         }
         Trigger::EventAndFlag { event: event_name, flag, equals } => {
-            if !event_matches_name(event, event_name) {
+            if !event_matches_name(context.event, event_name) {
                 return false;
             }
             // Check if flag matches expected value
-            match story_flags.get(flag) {
+            match context.story_flags.get(flag) {
                 Some(current_value) => current_value == equals,
                 None => false,
             }
         }
+        Trigger::Flag(condition) => context.story_flags.check(condition),
+        Trigger::HealthBelow(value) => context.health.current < *value,
+        Trigger::HealthAtLeast(value) => context.health.current >= *value,
+        Trigger::HasKey(name) => context.inventory.has_key(name),
+        Trigger::NpcPresent(name) => context.npcs.iter().any(|npc| npc.eq_ignore_ascii_case(name)),
+        Trigger::All(triggers) => triggers.iter().all(|t| trigger_matches(t, context)),
+        Trigger::Any(triggers) => triggers.iter().any(|t| trigger_matches(t, context)),
+        Trigger::Not(trigger) => !trigger_matches(trigger, context),
     }
 }
 
 fn event_matches_name(event: &LevelEvent, name: &str) -> bool {
-    match event {
-        LevelEvent::ProjectilesDone => name == "ProjectilesDone",
-        LevelEvent::DialogueComplete => name == "DialogueComplete",
-        LevelEvent::BossDefeated => name == "BossDefeated",
-    }
+    event.name() == name
+}
+
+/// Everything an `Action` can touch, shared by reactions and dialogue choices
+#[derive(SystemParam)]
+pub struct ActionRunner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub story_flags: ResMut<'w, StoryFlags>,
+    pub inventory: ResMut<'w, Inventory>,
+    pub attack_timer: ResMut<'w, AttackTimer>,
+    pub current_level: ResMut<'w, CurrentLevel>,
+    pub next_state: ResMut<'w, NextState<GameState>>,
+    current_state: Res<'w, State<GameState>>,
+    pub queued_dialogue: ResMut<'w, QueuedDialogue>,
+    pub scheduled: ResMut<'w, ScheduledActions>,
+    item_registry: Res<'w, ItemRegistry>,
+    tagged_query: Query<'w, 's, (Entity, &'static Tagged, &'static Transform)>,
 }
 
 pub fn process_reactions(
     mut events: MessageReader<LevelEvent>,
     loaded_data: Res<LoadedLevelData>,
-    mut commands: Commands,
-    mut story_flags: ResMut<StoryFlags>,
-    mut attack_timer: ResMut<AttackTimer>,
-    mut current_level: ResMut<CurrentLevel>,
-    mut next_state: ResMut<NextState<GameState>>,
-    mut queued_dialogue: ResMut<QueuedDialogue>,
-    arena_query: Query<Entity, With<PlayerArena>>,
-    wall_query: Query<Entity, With<Wall>>,
+    health: Res<PlayerHealth>,
+    npc_query: Query<&Npc>,
+    mut fired: ResMut<FiredReactions>,
+    mut runner: ActionRunner,
 ) {
     for event in events.read() {
         info!("LevelEvent fired: {:?}", event);
 
         let Some(level_data) = &loaded_data.0 else { continue };
+        let level_id = runner.current_level.level_id.clone();
+        let reaction_id = |index: usize| format!("{}/{}", level_id, index);
 
-        // Only one reaction should fire per event - break after first match
-        for reaction in &level_data.reactions {
-            if trigger_matches(&reaction.trigger, event, &story_flags) {
-                info!("Reaction triggered: {:?}", reaction.trigger);
-
-                for action in &reaction.actions {
-                    execute_action(
-                        action,
-                        &mut commands,
-                        &mut story_flags,
-                        &mut attack_timer,
-                        &mut current_level,
-                        &mut next_state,
-                        &mut queued_dialogue,
-                        &arena_query,
-                        &wall_query,
-                    );
-                }
-                break; // Only fire one reaction per event
+        // Check every trigger before running anything, so one reaction's actions
+        // (e.g. bumping boss_phase) can't make the next one match the same event
+        let npcs: Vec<String> = npc_query.iter().map(|npc| npc.name.clone()).collect();
+        let context = TriggerContext {
+            event,
+            story_flags: &runner.story_flags,
+            health: &health,
+            inventory: &runner.inventory,
+            npcs: &npcs,
+        };
+        let mut matching: Vec<(usize, &Reaction)> = level_data.reactions
+            .iter()
+            .enumerate()
+            .filter(|(index, reaction)| {
+                !(reaction.once && fired.0.contains(&reaction_id(*index)))
+                    && trigger_matches(&reaction.trigger, &context)
+            })
+            .collect();
+        // Stable sort, so equal priorities keep file order
+        matching.sort_by_key(|(_, reaction)| std::cmp::Reverse(reaction.priority));
+
+        for (index, reaction) in matching {
+            info!("Reaction triggered: {:?}", reaction.trigger);
+            if reaction.once {
+                fired.0.insert(reaction_id(index));
+            }
+            runner.run(&reaction.actions);
+            if !reaction.fallthrough {
+                break;
             }
         }
     }
 }
 
-fn execute_action(
-    action: &Action,
-    commands: &mut Commands,
-    story_flags: &mut ResMut<StoryFlags>,
-    attack_timer: &mut ResMut<AttackTimer>,
-    current_level: &mut ResMut<CurrentLevel>,
-    next_state: &mut ResMut<NextState<GameState>>,
-    queued_dialogue: &mut ResMut<QueuedDialogue>,
-    arena_query: &Query<Entity, With<PlayerArena>>,
-    wall_query: &Query<Entity, With<Wall>>,
-) {
-    match action {
-        Action::DespawnArena => {
-            info!("Executing: DespawnArena");
-            // Despawn arena marker
-            for entity in arena_query.iter() {
-                commands.entity(entity).despawn();
-            }
-            // Despawn all walls (arena walls are the only walls in boss fight)
-            for entity in wall_query.iter() {
-                commands.entity(entity).despawn();
+pub fn run_scheduled_actions(time: Res<Time>, mut runner: ActionRunner) {
+    for actions in runner.scheduled.tick(time.delta()) {
+        runner.run(&actions);
+    }
+}
+
+impl ActionRunner<'_, '_> {
+    /// Run an action list in order; a `Wait` schedules the rest of the list for later
+    pub fn run(&mut self, actions: &[Action]) {
+        for (i, action) in actions.iter().enumerate() {
+            if let Action::Wait { seconds } = action {
+                info!("Executing: Wait({}s, {} actions left)", seconds, actions.len() - i - 1);
+                if i + 1 < actions.len() {
+                    self.scheduled.after(*seconds, actions[i + 1..].to_vec());
+                }
+                return;
             }
+            self.execute(action);
         }
+    }
 
-        Action::SetFlag { key, value } => {
-            info!("Executing: SetFlag({} = {:?})", key, value);
-            story_flags.set(key, value.clone());
-        }
+    fn execute(&mut self, action: &Action) {
+        match action {
+            Action::DespawnArena => {
+                info!("Executing: DespawnArena");
+                self.despawn_tagged(ARENA_TAG);
+            }
 
-        Action::QueueDialogue { lines, then } => {
-            info!("Executing: QueueDialogue ({} lines, then: {})", lines.len(), then);
-            queued_dialogue.lines = lines.clone();
-            queued_dialogue.then_state = then.clone();
-            next_state.set(GameState::Dialogue);
-        }
+            Action::SetFlag { key, value } => {
+                info!("Executing: SetFlag({} = {:?})", key, value);
+                self.story_flags.set(key, value.clone());
+            }
 
-        Action::SpawnDoor { position, leads_to, label } => {
-            info!("Executing: SpawnDoor at {:?} -> {}", position, leads_to);
-            commands.spawn((
-                Text2d::new(DOOR_ART),
-                TextFont { font_size: 6.0, ..default() },
-                TextColor(Color::WHITE),
-                Transform::from_translation(Vec3::new(position.0, position.1, 1.0)),
-                LevelDoor { leads_to: leads_to.clone() },
-                HitBox { width: 80.0, height: 120.0 },
-                LevelEntity,
-            ));
-            info!("Spawned door '{}' at ({}, {})", label, position.0, position.1);
-        }
+            Action::QueueDialogue { lines, then } => {
+                info!("Executing: QueueDialogue ({} lines, then: {})", lines.len(), then);
+                self.queued_dialogue.lines = lines.clone();
+                self.queued_dialogue.then_state = then.clone();
+                if *self.current_state.get() == GameState::Dialogue {
+                    // Re-entering Dialogue would clear these lines on the way out, so the open
+                    // panel switches to them instead. They also outlast a dialogue that just finished.
+                    self.queued_dialogue.restart = true;
+                    self.next_state.reset();
+                } else {
+                    self.next_state.set(GameState::Dialogue);
+                }
+            }
 
-        Action::RestartProjectiles { count } => {
-            info!("Executing: RestartProjectiles({})", count);
-            attack_timer.projectiles_fired = 0;
-            attack_timer.max_projectiles = *count;
-            attack_timer.event_sent = false;
-        }
+            Action::SpawnDoor { position, leads_to, label } => {
+                info!("Executing: SpawnDoor at {:?} -> {}", position, leads_to);
+                spawn_door_from_data(&mut self.commands, &DoorData {
+                    position: *position,
+                    leads_to: leads_to.clone(),
+                    leads_to_if: Vec::new(),
+                    label: label.clone(),
+                    locked: false,
+                    key_required: None,
+                    consume_key: false,
+                    extra: Vec::new(),
+                    show_label: false,
+                    hints: Vec::new(),
+                });
+            }
+
+            Action::RestartProjectiles { count } => {
+                info!("Executing: RestartProjectiles({})", count);
+                self.attack_timer.projectiles_fired = 0;
+                self.attack_timer.max_projectiles = *count;
+                self.attack_timer.event_sent = false;
+            }
+
+            Action::TransitionToLevel { level_id } => {
+                info!("Executing: TransitionToLevel({})", level_id);
+                self.current_level.level_id = level_id.clone();
+                self.current_level.loaded = false;
+                self.next_state.set(GameState::LoadingNewLevel);
+            }
+
+            Action::SetNextLevel { level_id } => {
+                info!("Executing: SetNextLevel({})", level_id);
+                self.current_level.level_id = level_id.clone();
+                self.current_level.loaded = false;
+                // Don't transition - let QueueDialogue's then_state handle it
+            }
 
-        Action::TransitionToLevel { level_id } => {
-            info!("Executing: TransitionToLevel({})", level_id);
-            current_level.level_id = level_id.clone();
-            current_level.loaded = false;
-            next_state.set(GameState::LoadingNewLevel);
+            Action::GiveKey { name } => {
+                info!("Executing: GiveKey({})", name);
+                self.inventory.add_key(name);
+            }
+
+            // Handled by run(), which holds back the rest of the list
+            Action::Wait { .. } => {}
+
+            Action::After { seconds, actions } => {
+                info!("Executing: After({}s, {} actions)", seconds, actions.len());
+                self.scheduled.after(*seconds, actions.clone());
+            }
+
+            Action::Repeat { times, interval, actions } => {
+                info!("Executing: Repeat({} times, every {}s)", times, interval);
+                if *times == 0 {
+                    return;
+                }
+                self.run(actions);
+                if *times > 1 {
+                    self.scheduled.every(*interval, *times, actions.clone());
+                }
+            }
+
+            Action::SpawnEntity { entity, tag } => {
+                info!("Executing: SpawnEntity {:?} (tag: {:?})", entity, tag);
+                let spawned = match entity {
+                    EntityData::Npc(npc) => spawn_npc_from_data(&mut self.commands, npc, &self.story_flags).into_iter().collect(),
+                    EntityData::Item(item) => vec![spawn_item_from_data(&mut self.commands, item, None, &self.item_registry)],
+                    EntityData::Door(door) => vec![spawn_door_from_data(&mut self.commands, door)],
+                    EntityData::Wall { from, to } => spawn_wall_segment(&mut self.commands, *from, *to),
+                };
+                if let Some(tag) = tag {
+                    for entity in spawned {
+                        self.commands.entity(entity).insert(Tagged(tag.clone()));
+                    }
+                }
+            }
+
+            Action::DespawnTagged { tag } => {
+                info!("Executing: DespawnTagged({})", tag);
+                self.despawn_tagged(tag);
+            }
+
+            Action::ModifyTagged { tag, move_to, extra } => {
+                info!("Executing: ModifyTagged({})", tag);
+                let tagged: Vec<(Entity, Vec3)> = self.tagged_query
+                    .iter()
+                    .filter(|(_, tagged, _)| tagged.0 == *tag)
+                    .map(|(entity, _, transform)| (entity, transform.translation))
+                    .collect();
+                for (entity, translation) in tagged {
+                    if let Some((x, y)) = move_to {
+                        self.commands.entity(entity).insert(Transform::from_xyz(*x, *y, translation.z));
+                    }
+                    insert_extra_components(&mut self.commands, entity, extra);
+                }
+            }
         }
+    }
 
-        Action::SetNextLevel { level_id } => {
-            info!("Executing: SetNextLevel({})", level_id);
-            current_level.level_id = level_id.clone();
-            current_level.loaded = false;
-            // Don't transition - let QueueDialogue's then_state handle it
+    fn despawn_tagged(&mut self, tag: &str) {
+        for (entity, tagged, _) in &self.tagged_query {
+            if tagged.0 == tag {
+                self.commands.entity(entity).despawn();
+            }
         }
     }
 }
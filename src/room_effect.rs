@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::inventory::Inventory;
+use crate::level::{CurrentLevel, LoadedLevelData};
+use crate::level_schema::{LastingEffect, RoomEffect};
+use crate::player::{GodMode, PlayerHealth, PlayerModifiers};
+use crate::state::GameState;
+use crate::story_flags::StoryFlags;
+use crate::ui::{despawn_effects_ui, spawn_effects_ui, update_effects_ui};
+
+/// Buffs and curses picked up from rooms' `on_enter`, with the rooms they have left
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct ActiveEffects(pub Vec<ActiveEffect>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveEffect {
+    pub name: String,
+    pub curse: bool,
+    pub effect: LastingEffect,
+    /// Counting the current room
+    pub rooms_left: u32,
+}
+
+impl ActiveEffects {
+    /// Product of every active `Speed`
+    pub fn speed_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .filter_map(|active| match active.effect {
+                LastingEffect::Speed(multiplier) => Some(multiplier),
+                _ => None,
+            })
+            .product()
+    }
+
+    /// Start a buff or curse; one with the same name is topped back up instead of stacking
+    fn add(&mut self, name: &str, curse: bool, rooms: u32, effect: &LastingEffect) {
+        if rooms == 0 {
+            return;
+        }
+        self.0.retain(|active| active.name != name);
+        self.0.push(ActiveEffect { name: name.to_string(), curse, effect: effect.clone(), rooms_left: rooms });
+    }
+
+    /// A room was left: everything has one room less, and whatever ran out is dropped
+    fn age(&mut self) {
+        for active in &mut self.0 {
+            active.rooms_left = active.rooms_left.saturating_sub(1);
+        }
+        self.0.retain(|active| {
+            if active.rooms_left == 0 {
+                info!("{} wore off", active.name);
+            }
+            active.rooms_left > 0
+        });
+    }
+}
+
+// Once the room is in place: age what's active, apply the room's `on_enter`, then the
+// per-room part of every buff and curse (new ones included)
+#[allow(clippy::too_many_arguments)]
+pub fn apply_room_effects(
+    current_level: Res<CurrentLevel>,
+    loaded_data: Res<LoadedLevelData>,
+    mut active: ResMut<ActiveEffects>,
+    mut health: ResMut<PlayerHealth>,
+    mut modifiers: ResMut<PlayerModifiers>,
+    mut inventory: ResMut<Inventory>,
+    mut story_flags: ResMut<StoryFlags>,
+    god_mode: Res<GodMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Leaving for LoadError: no room was entered
+    if !current_level.loaded {
+        return;
+    }
+    let Some(level_data) = &loaded_data.0 else { return };
+
+    active.age();
+    let mut damage: i8 = 0;
+    for effect in &level_data.on_enter {
+        info!("Room effect: {:?}", effect);
+        match effect {
+            RoomEffect::Heal(amount) => health.current = health.current.saturating_add((*amount).max(0)).min(health.max),
+            RoomEffect::Damage(amount) => damage = damage.saturating_add((*amount).max(0)),
+            RoomEffect::MaxHealth(amount) => {
+                health.max = health.max.saturating_add(*amount).max(1);
+                health.current = health.current.saturating_add((*amount).max(0)).min(health.max);
+            }
+            RoomEffect::SpeedMultiplier(multiplier) => modifiers.speed_multiplier *= multiplier,
+            RoomEffect::GrantKey(name) => inventory.add_key(name),
+            RoomEffect::SetFlag { key, value } => story_flags.set(key, value.clone()),
+            RoomEffect::Buff { name, rooms, effect } => active.add(name, false, *rooms, effect),
+            RoomEffect::Curse { name, rooms, effect } => active.add(name, true, *rooms, effect),
+        }
+    }
+    for effect in &active.0 {
+        match effect.effect {
+            LastingEffect::DamagePerRoom(amount) => damage = damage.saturating_add(amount.max(0)),
+            LastingEffect::HealPerRoom(amount) => health.current = health.current.saturating_add(amount.max(0)).min(health.max),
+            LastingEffect::Speed(_) => {}
+        }
+    }
+
+    if damage > 0 && !god_mode.0 {
+        health.current = (health.current - damage).max(0);
+        info!("Room dealt {} damage ({}/{})", damage, health.current, health.max);
+        if health.current == 0 {
+            next_state.set(GameState::Defeat);
+        }
+    }
+}
+
+pub struct RoomEffectPlugin;
+
+impl Plugin for RoomEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveEffects>()
+           .add_systems(OnExit(GameState::LoadingNewLevel), (apply_room_effects, spawn_effects_ui).chain())
+           .add_systems(Update, update_effects_ui.run_if(resource_changed::<ActiveEffects>))
+           .add_systems(OnEnter(GameState::LoadingNewLevel), despawn_effects_ui)
+           .add_systems(OnEnter(GameState::Defeat), despawn_effects_ui);
+    }
+}
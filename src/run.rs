@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::item::CollectedItems;
+use crate::level::CurrentLevel;
+use crate::level_schema::{DoorData, DoorHint, LevelData};
+use crate::run_schema::RunData;
+use crate::story_flags::{FlagValue, StoryFlags};
+
+/// Run the title menu starts (`assets/runs/default.run.ron`)
+pub const DEFAULT_RUN: &str = "default";
+/// Number flag holding how deep into the run the current room is (the start is 0)
+pub const RUN_DEPTH_FLAG: &str = "run_depth";
+
+/// One room of a run: the level file it's built from, and the label and hints of the doors
+/// leading to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRoom {
+    pub level: String,
+    pub label: String,
+    #[serde(default)]
+    pub hints: Vec<DoorHint>,
+}
+
+/// Every room a run can go through. Row 0 is the start; door `n` of any room in a row leads
+/// to room `n` of the next row, and the last row is the boss.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunPlan {
+    pub rooms: Vec<Vec<RunRoom>>,
+}
+
+impl RunPlan {
+    /// Same run file and seed, same plan
+    pub fn generate(data: &RunData, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut rooms = vec![vec![RunRoom { level: data.start.clone(), label: "Start".to_string(), hints: Vec::new() }]];
+        for depth in 1..=data.length {
+            let row: Vec<RunRoom> = (0..data.doors.max(1)).filter_map(|_| pick_room(data, depth, &mut rng)).collect();
+            if row.is_empty() {
+                warn!("Run '{}' has no room that can go at depth {}, going straight to the boss", data.name, depth);
+                break;
+            }
+            rooms.push(row);
+        }
+        if let Some(level) = pick_level(&data.boss, &mut rng) {
+            rooms.push(vec![RunRoom { level, label: "Boss".to_string(), hints: Vec::new() }]);
+        }
+        Self { rooms }
+    }
+
+    /// Depth of the last room
+    pub fn depth(&self) -> u32 {
+        self.rooms.len().saturating_sub(1) as u32
+    }
+}
+
+// Weighted pick of a pool for this depth, then any of its levels
+fn pick_room(data: &RunData, depth: u32, rng: &mut StdRng) -> Option<RunRoom> {
+    let weights: Vec<f32> = data.pools.iter().map(|pool| pool.weight_at(depth)).collect();
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.random_range(0.0..total);
+    let mut picked = None;
+    for (pool, weight) in data.pools.iter().zip(&weights) {
+        if *weight <= 0.0 {
+            continue;
+        }
+        picked = Some(pool);
+        if roll < *weight {
+            break;
+        }
+        roll -= weight;
+    }
+    let pool = picked?;
+    Some(RunRoom { level: pick_level(&pool.levels, rng)?, label: pool.label.clone(), hints: pool.hints.clone() })
+}
+
+fn pick_level(levels: &[String], rng: &mut StdRng) -> Option<String> {
+    if levels.is_empty() {
+        return None;
+    }
+    Some(levels[rng.random_range(0..levels.len())].clone())
+}
+
+/// A run in progress. Only the seed, plan and run flags are kept, so a save resumes the same
+/// run even if the run file changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub seed: u64,
+    pub plan: RunPlan,
+    /// `RunData.run_flags`
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+impl Run {
+    pub fn new(data: &RunData, seed: u64) -> Self {
+        Self { seed, plan: RunPlan::generate(data, seed), flags: data.run_flags.clone() }
+    }
+
+    /// Id of the room the run starts in
+    pub fn start_id(&self) -> String {
+        run_room_id(&self.plan.rooms[0][0].level, 0)
+    }
+
+    /// Seed for a cave room at this depth that doesn't fix its own
+    pub fn cave_seed(&self, depth: u32) -> u64 {
+        StdRng::seed_from_u64(self.seed.wrapping_add(depth as u64)).random()
+    }
+
+    /// Point a run room's doors at the next row of the plan. The template's doors lend their
+    /// position, lock, components and hints in order (the pool's hints win); doors past those
+    /// are spread along the top of the room. The boss room keeps the doors in its file.
+    pub fn prepare_room(&self, depth: u32, level: &mut LevelData) {
+        if let Some(next) = self.plan.rooms.get(depth as usize + 1) {
+            let template = std::mem::take(&mut level.doors);
+            level.doors = next
+                .iter()
+                .enumerate()
+                .map(|(door, room)| {
+                    let mut data = template.get(door).cloned().unwrap_or_else(|| DoorData {
+                        position: spread_door(door, next.len()),
+                        leads_to: String::new(),
+                        leads_to_if: Vec::new(),
+                        label: String::new(),
+                        locked: false,
+                        key_required: None,
+                        consume_key: true,
+                        extra: Vec::new(),
+                        show_label: false,
+                        hints: Vec::new(),
+                    });
+                    data.leads_to = run_room_id(&room.level, depth + 1);
+                    data.leads_to_if.clear();
+                    data.label = room.label.clone();
+                    if !room.hints.is_empty() {
+                        data.hints = room.hints.clone();
+                    }
+                    data
+                })
+                .collect();
+        }
+
+        if level.room_type == "cave" {
+            let cave = level.cave.get_or_insert_with(Default::default);
+            if cave.seed.is_none() {
+                cave.seed = Some(self.cave_seed(depth));
+            }
+        }
+    }
+}
+
+fn spread_door(door: usize, doors: usize) -> (f32, f32) {
+    ((door as f32 + 0.5 - doors as f32 / 2.0) * 250.0, 250.0)
+}
+
+/// Id of a run room: its level file and depth, e.g. "run_trap@3". Every room of a run gets its
+/// own id, so a template used twice still has its items and one-shot reactions the second time.
+pub fn run_room_id(level: &str, depth: u32) -> String {
+    format!("{}@{}", level, depth)
+}
+
+/// Level file and depth of a run room id, None for ordinary levels
+pub fn split_run_room_id(id: &str) -> Option<(&str, u32)> {
+    let (level, depth) = id.rsplit_once('@')?;
+    Some((level, depth.parse().ok()?))
+}
+
+/// Level file an id is loaded from (a run room's template, otherwise the id itself)
+pub fn level_file(id: &str) -> &str {
+    split_run_room_id(id).map_or(id, |(level, _)| level)
+}
+
+/// The run being played, if any
+#[derive(Resource, Default)]
+pub struct CurrentRun(pub Option<Run>);
+
+/// The run file new runs are generated from
+#[derive(Resource, Default)]
+pub struct DefaultRun(pub Handle<RunData>);
+
+/// Start a run in its first room. The caller then moves to LoadingNewLevel.
+pub fn start_run(current_run: &mut CurrentRun, current_level: &mut CurrentLevel, data: &RunData, seed: u64) {
+    let run = Run::new(data, seed);
+    info!("Starting run '{}' with seed {} ({} rooms deep)", data.name, seed, run.plan.depth());
+    current_level.level_id = run.start_id();
+    current_level.loaded = false;
+    current_run.0 = Some(run);
+}
+
+/// Start the run over from its first room after a defeat. The seed stays, so it's the same
+/// rooms, but the run's flags go and its items are back. The caller resets the player.
+pub fn restart_run(run: &Run, current_level: &mut CurrentLevel, story_flags: &mut StoryFlags, collected: &mut CollectedItems) {
+    info!("Restarting run with seed {}", run.seed);
+    current_level.level_id = run.start_id();
+    current_level.loaded = false;
+    story_flags.remove(RUN_DEPTH_FLAG);
+    for flag in &run.flags {
+        story_flags.remove(flag);
+    }
+    // Item ids are "<level_id>/<index>"
+    collected.0.retain(|id| id.rsplit_once('/').is_none_or(|(level, _)| split_run_room_id(level).is_none()));
+}
+
+/// Fit a room that's loading into the run: run rooms get their doors and cave seed from the
+/// plan and set `run_depth`; going anywhere else ends the run.
+pub fn enter_run_room(current_run: &mut CurrentRun, level_id: &str, level: &mut LevelData, story_flags: &mut StoryFlags) {
+    let Some(run) = &current_run.0 else { return };
+    match split_run_room_id(level_id) {
+        Some((_, depth)) => {
+            run.prepare_room(depth, level);
+            story_flags.set(RUN_DEPTH_FLAG, FlagValue::Number(depth as i32));
+        }
+        None => {
+            info!("Run with seed {} is over ({} is not part of it)", run.seed, level_id);
+            current_run.0 = None;
+        }
+    }
+}
+
+/// A run file read straight from disk (for tools)
+pub struct RunFile {
+    /// File name without `.run.ron`
+    pub file_id: String,
+    pub data: Result<RunData, String>,
+}
+
+/// Every `*.run.ron` in a runs directory, sorted by file name (none if the directory is missing)
+pub fn read_runs(runs_dir: &Path) -> io::Result<Vec<RunFile>> {
+    let entries = match fs::read_dir(runs_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut runs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(file_id) = path.file_name().and_then(|name| name.to_str()).and_then(|name| name.strip_suffix(".run.ron")) else {
+            continue;
+        };
+        let data = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| ron::from_str::<RunData>(&text).map_err(|err| err.to_string()));
+        runs.push(RunFile { file_id: file_id.to_string(), data });
+    }
+    runs.sort_by(|a, b| a.file_id.cmp(&b.file_id));
+    Ok(runs)
+}
+
+fn load_default_run(asset_server: Res<AssetServer>, mut default_run: ResMut<DefaultRun>) {
+    default_run.0 = asset_server.load(format!("runs/{}.run.ron", DEFAULT_RUN));
+}
+
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        // Rooms are laid out for the run as they load (loading_new_level.rs)
+        app.add_plugins(RonAssetPlugin::<RunData>::new(&["run.ron"]))
+           .init_resource::<CurrentRun>()
+           .init_resource::<DefaultRun>()
+           .add_systems(Startup, load_default_run);
+    }
+}
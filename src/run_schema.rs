@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::level_schema::DoorHint;
+
+/// A generated door run, loaded from `assets/runs/<id>.run.ron` (see `run.rs`)
+#[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
+pub struct RunData {
+    pub name: String,
+    /// Level the run starts in (depth 0)
+    pub start: String,
+    /// Rooms picked from the pools between the start and the boss
+    pub length: u32,
+    /// Doors each room offers
+    pub doors: u32,
+    pub pools: Vec<RoomPool>,
+    /// Levels the last room is picked from
+    pub boss: Vec<String>,
+    /// Story flags that belong to the run (its coins, what was bought in it), dropped when
+    /// the run starts over. `run_depth` always is.
+    #[serde(default)]
+    pub run_flags: Vec<String>,
+}
+
+/// Room templates a door can lead to, e.g. the trap rooms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomPool {
+    /// Label of the doors leading here ("Trap", "Shop"), and what `DoorOpened` carries
+    pub label: String,
+    /// Chance of the pool at depth 1, against the other pools' weights
+    pub weight: f32,
+    /// Added to `weight` for every room deeper, so pools can come and go as the run goes on
+    #[serde(default)]
+    pub weight_per_depth: f32,
+    /// Shallowest depth the pool can show up at
+    #[serde(default)]
+    pub min_depth: u32,
+    /// Level files, picked from evenly
+    pub levels: Vec<String>,
+    /// Given to the doors leading here (the template's own hints are kept when empty)
+    #[serde(default)]
+    pub hints: Vec<DoorHint>,
+}
+
+impl RoomPool {
+    /// Weight at a depth (0 when it can't show up there)
+    pub fn weight_at(&self, depth: u32) -> f32 {
+        if depth < self.min_depth || self.levels.is_empty() {
+            return 0.0;
+        }
+        (self.weight + self.weight_per_depth * depth.saturating_sub(1) as f32).max(0.0)
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::inventory::Inventory;
+use crate::item::CollectedItems;
+use crate::level::{CurrentLevel, RunHistory};
+use crate::player::{PlayerHealth, PlayerModifiers};
+use crate::reaction::FiredReactions;
+use crate::run::{CurrentRun, Run};
+use crate::room_effect::ActiveEffects;
+use crate::story_flags::StoryFlags;
+
+/// Bump when the save format changes, and teach `migrate` how to read the old one
+pub const SAVE_VERSION: u32 = 1;
+pub const SAVE_SLOTS: u8 = 3;
+
+/// Which save slot autosaves go to
+#[derive(Resource)]
+pub struct SaveSlot(pub u8);
+
+impl Default for SaveSlot {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Where save slots are read from and written to (tests point this at a temp dir)
+#[derive(Resource)]
+pub struct SaveDir(pub PathBuf);
+
+impl Default for SaveDir {
+    fn default() -> Self {
+        Self(save_dir())
+    }
+}
+
+/// Everything needed to resume a run
+#[derive(Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    pub level_id: String,
+    pub health: PlayerHealth,
+    pub modifiers: PlayerModifiers,
+    pub story_flags: StoryFlags,
+    pub inventory: Inventory,
+    pub history: RunHistory,
+    pub collected_items: HashSet<String>,
+    /// One-shot reactions already used up (missing in older v1 saves)
+    #[serde(default)]
+    pub fired_reactions: HashSet<String>,
+    /// Seed and rooms of the run being played, if any (missing in older v1 saves)
+    #[serde(default)]
+    pub run: Option<Run>,
+    /// Buffs and curses from rooms (missing in older v1 saves)
+    #[serde(default)]
+    pub active_effects: ActiveEffects,
+}
+
+// Only the version is read first, so we can refuse or migrate before parsing the rest
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Parse(String),
+    /// Written by a newer version of the game
+    TooNew(u32),
+    /// Older format we no longer know how to read
+    Unsupported(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "could not read save: {}", err),
+            SaveError::Parse(err) => write!(f, "corrupt save: {}", err),
+            SaveError::TooNew(version) => write!(f, "save is from a newer version (v{})", version),
+            SaveError::Unsupported(version) => write!(f, "save format v{} is no longer supported", version),
+        }
+    }
+}
+
+/// Saves live in the per-user data directory (e.g. ~/.local/share/doorgame/saves)
+pub fn save_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("doorgame"))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("saves")
+}
+
+pub fn slot_path(dir: &Path, slot: u8) -> PathBuf {
+    dir.join(format!("slot_{}.ron", slot))
+}
+
+pub fn slot_exists(dir: &Path, slot: u8) -> bool {
+    slot_path(dir, slot).exists()
+}
+
+/// Move a save this version can't read out of the way (to `slot_<n>.ron.bak`), so starting
+/// over in its slot doesn't destroy it
+pub fn back_up_slot(dir: &Path, slot: u8) -> Result<PathBuf, SaveError> {
+    let path = slot_path(dir, slot);
+    let backup = path.with_extension("ron.bak");
+    fs::rename(&path, &backup).map_err(SaveError::Io)?;
+    Ok(backup)
+}
+
+pub fn load_slot(dir: &Path, slot: u8) -> Result<SaveData, SaveError> {
+    let text = fs::read_to_string(slot_path(dir, slot)).map_err(SaveError::Io)?;
+    let header: SaveHeader = ron::from_str(&text).map_err(|err| SaveError::Parse(err.to_string()))?;
+
+    match header.version {
+        SAVE_VERSION => ron::from_str(&text).map_err(|err| SaveError::Parse(err.to_string())),
+        version if version > SAVE_VERSION => Err(SaveError::TooNew(version)),
+        version => migrate(version, &text),
+    }
+}
+
+/// Read a save written in an older format and bring it up to the current one. Each old
+/// version is parsed with its own layout: `ron::Value` would lose enum variant names.
+fn migrate(version: u32, _text: &str) -> Result<SaveData, SaveError> {
+    // No older format can be read yet; add a step here when SAVE_VERSION is bumped
+    Err(SaveError::Unsupported(version))
+}
+
+pub fn write_slot(dir: &Path, slot: u8, data: &SaveData) -> Result<(), SaveError> {
+    let text = ron::ser::to_string_pretty(data, ron::ser::PrettyConfig::default())
+        .map_err(|err| SaveError::Parse(err.to_string()))?;
+    fs::create_dir_all(dir).map_err(SaveError::Io)?;
+    // Write then rename so a crash mid-save doesn't destroy the old one
+    let path = slot_path(dir, slot);
+    let tmp = path.with_extension("ron.tmp");
+    fs::write(&tmp, text).map_err(SaveError::Io)?;
+    fs::rename(&tmp, &path).map_err(SaveError::Io)
+}
+
+// Autosave every time we head into a new room
+#[allow(clippy::too_many_arguments)]
+pub fn autosave(
+    dir: Res<SaveDir>,
+    slot: Res<SaveSlot>,
+    current_level: Res<CurrentLevel>,
+    health: Res<PlayerHealth>,
+    modifiers: Res<PlayerModifiers>,
+    story_flags: Res<StoryFlags>,
+    inventory: Res<Inventory>,
+    history: Res<RunHistory>,
+    collected: Res<CollectedItems>,
+    fired: Res<FiredReactions>,
+    run: Res<CurrentRun>,
+    active_effects: Res<ActiveEffects>,
+) {
+    let data = SaveData {
+        version: SAVE_VERSION,
+        level_id: current_level.level_id.clone(),
+        health: health.clone(),
+        modifiers: modifiers.clone(),
+        story_flags: story_flags.clone(),
+        inventory: inventory.clone(),
+        history: history.clone(),
+        collected_items: collected.0.clone(),
+        fired_reactions: fired.0.clone(),
+        run: run.0.clone(),
+        active_effects: active_effects.clone(),
+    };
+
+    match write_slot(&dir.0, slot.0, &data) {
+        Ok(()) => info!("Autosaved to slot {} ({})", slot.0, data.level_id),
+        Err(err) => warn!("Autosave to slot {} failed: {}", slot.0, err),
+    }
+}
+
+/// Put a loaded save back into the world. The caller then transitions to LoadingNewLevel.
+pub fn apply_save(commands: &mut Commands, current_level: &mut CurrentLevel, data: SaveData) {
+    info!("Restoring save at {} ({} rooms visited)", data.level_id, data.history.visited.len());
+    current_level.level_id = data.level_id;
+    current_level.loaded = false;
+    commands.insert_resource(data.health);
+    commands.insert_resource(data.modifiers);
+    commands.insert_resource(data.story_flags);
+    commands.insert_resource(data.inventory);
+    commands.insert_resource(data.history);
+    commands.insert_resource(CollectedItems(data.collected_items));
+    commands.insert_resource(FiredReactions(data.fired_reactions));
+    commands.insert_resource(CurrentRun(data.run));
+    commands.insert_resource(data.active_effects);
+}
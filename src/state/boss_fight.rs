@@ -1,15 +1,18 @@
 //on enter system -> spawn arena that confines player -> spawn boss -> start attacks some how
 use bevy::prelude::*;
-use rand::Rng;
-use crate::art::SCARY_DOOR_ART;
-use crate::level_entity::LevelEntity;
+use std::time::Duration;
+use crate::boss_schema::{BossData, BossPhase};
+use crate::bullet_pattern::{fire_pattern, PatternTarget};
+use crate::level_entity::{LevelEntity, Tagged};
 use crate::level::spawn_wall_at;
 use crate::player::Player;
-use crate::projectile::spawn_projectile_at;
+use crate::projectile::ProjectilePool;
 use crate::level_event::LevelEvent;
 use crate::story_flags::{FlagValue, StoryFlags};
 
 const WALL_SIZE: f32 = 32.0;
+/// Tag on the arena and its walls, so `DespawnArena` leaves the room's own walls alone
+pub const ARENA_TAG: &str = "arena";
 
 #[derive(Component)]
 pub struct PlayerArena {
@@ -26,6 +29,14 @@ pub struct AttackTimer {
     pub projectiles_fired: u32,
     pub max_projectiles: u32,
     pub event_sent: bool,
+    /// BossDefeated has been sent for this fight
+    pub boss_defeated: bool,
+    /// Phase the pattern sequence below belongs to
+    pub phase: i32,
+    /// Next entry in the phase's pattern list
+    pub step: usize,
+    /// Current rotation of Spiral patterns (radians)
+    pub spiral_angle: f32,
 }
 
 impl Default for AttackTimer {
@@ -35,70 +46,137 @@ impl Default for AttackTimer {
             projectiles_fired: 0,
             max_projectiles: 15,
             event_sent: false,
+            boss_defeated: false,
+            phase: 1,
+            step: 0,
+            spiral_angle: 0.0,
         }
     }
 }
 
+/// Boss file requested for the level being loaded (LevelData.boss)
+#[derive(Resource, Default)]
+pub struct CurrentBoss {
+    pub handle: Option<Handle<BossData>>,
+    pub ready: bool,
+}
+
+/// Boss definition for the current level, once loaded. None means the level has no boss file.
+#[derive(Resource, Default)]
+pub struct LoadedBossData(pub Option<BossData>);
+
+impl LoadedBossData {
+    pub fn phase(&self, number: i32) -> BossPhase {
+        self.0.as_ref().map(|boss| boss.phase(number)).unwrap_or_default()
+    }
+}
+
 /// Tracks whether the boss fight has been initialized (to avoid re-running setup on re-entry from dialogue)
 #[derive(Resource, Default)]
 pub struct BossFightInitialized(pub bool);
 
-pub fn reset_attack_timer(mut commands: Commands, initialized: Res<BossFightInitialized>) {
+/// A negative, NaN or infinite `interval`/`wait` from a boss file counts as 0 instead of
+/// panicking (doorgame-lint reports them)
+fn step_duration(seconds: f32) -> Duration {
+    Duration::try_from_secs_f32(seconds).unwrap_or(Duration::ZERO)
+}
+
+pub fn reset_attack_timer(
+    mut commands: Commands,
+    initialized: Res<BossFightInitialized>,
+    loaded_boss: Res<LoadedBossData>,
+) {
     if initialized.0 {
         info!("Boss fight already initialized, skipping attack timer reset");
         return;
     }
-    commands.insert_resource(AttackTimer::default());
+    let first_phase = loaded_boss.phase(1);
+    commands.insert_resource(AttackTimer {
+        timer: Timer::new(step_duration(first_phase.interval), TimerMode::Repeating),
+        max_projectiles: first_phase.count,
+        ..default()
+    });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn fire_projectiles_at_player(
     mut commands: Commands,
     time: Res<Time>,
     mut attack_timer: ResMut<AttackTimer>,
     player_query: Query<&Transform, With<Player>>,
     boss_query: Query<&Transform, With<Boss>>,
+    arena_query: Query<(&Transform, &PlayerArena)>,
     mut event_writer: MessageWriter<LevelEvent>,
+    loaded_boss: Res<LoadedBossData>,
+    story_flags: Res<StoryFlags>,
+    mut pool: ResMut<ProjectilePool>,
 ) {
+    let phase_number = story_flags.get_number("boss_phase").unwrap_or(1);
+
     // Don't fire if we've reached max - send event if not already sent
     if attack_timer.projectiles_fired >= attack_timer.max_projectiles {
         if !attack_timer.event_sent {
             info!("All projectiles fired, sending ProjectilesDone event");
             event_writer.write(LevelEvent::ProjectilesDone);
             attack_timer.event_sent = true;
+
+            // Surviving the last phase beats the boss (once, even if a reaction restarts the volleys)
+            let phase_count = loaded_boss.0.as_ref().map_or(1, |boss| boss.phases.len().max(1));
+            if phase_number as usize >= phase_count && !attack_timer.boss_defeated {
+                info!("Last phase survived, sending BossDefeated event");
+                event_writer.write(LevelEvent::BossDefeated);
+                attack_timer.boss_defeated = true;
+            }
         }
         return;
     }
 
+    // A new phase starts its pattern sequence from the top, at its own pace
+    let phase = loaded_boss.phase(phase_number);
+    if attack_timer.phase != phase_number {
+        attack_timer.phase = phase_number;
+        attack_timer.step = 0;
+        attack_timer.timer.set_duration(step_duration(phase.interval));
+    }
+
     attack_timer.timer.tick(time.delta());
 
     if attack_timer.timer.just_finished() {
         let Ok(player_transform) = player_query.single() else { return };
         let Ok(boss_transform) = boss_query.single() else { return };
 
-        let mut rng = rand::rng();
-
-        // Random spawn position near the boss
-        let spawn_offset_x = rng.random_range(-100.0..100.0);
-        let spawn_pos = Vec3::new(
-            boss_transform.translation.x + spawn_offset_x,
-            boss_transform.translation.y,
-            5.0,
-        );
-
-        // Direction toward player
-        let direction = Vec2::new(
-            player_transform.translation.x - spawn_pos.x,
-            player_transform.translation.y - spawn_pos.y,
-        ).normalize();
+        // Wall and Rain sweep the arena; once it's gone, fall back to the boss file's layout
+        let (arena_center, arena_size) = match arena_query.single() {
+            Ok((transform, arena)) => (transform.translation.truncate(), Vec2::new(arena.width, arena.height)),
+            Err(_) => {
+                let arena = loaded_boss.0.as_ref().map(|boss| boss.arena.clone()).unwrap_or_default();
+                (Vec2::from(arena.position), Vec2::from(arena.size))
+            }
+        };
+        let target = PatternTarget {
+            boss: boss_transform.translation.truncate(),
+            player: player_transform.translation.truncate(),
+            arena_center,
+            arena_size,
+        };
 
-        // Random speed
-        let speed = rng.random_range(150.0..300.0);
-        let velocity = direction * speed;
+        // Fire the next step, plus any that follow a zero wait (they go off together)
+        let mut next_wait = phase.interval;
+        for _ in 0..phase.patterns.len() {
+            let step = &phase.patterns[attack_timer.step % phase.patterns.len()];
+            fire_pattern(&mut commands, &mut pool, step, &target, &mut attack_timer.spiral_angle);
+            attack_timer.step += 1;
 
-        spawn_projectile_at(&mut commands, spawn_pos, velocity, "{=}");
+            let wait = step.wait.unwrap_or(phase.interval);
+            if wait > 0.0 {
+                next_wait = wait;
+                break;
+            }
+        }
+        attack_timer.timer.set_duration(step_duration(next_wait));
 
         attack_timer.projectiles_fired += 1;
-        info!("Fired projectile {}/{}", attack_timer.projectiles_fired, attack_timer.max_projectiles);
+        info!("Fired volley {}/{}", attack_timer.projectiles_fired, attack_timer.max_projectiles);
     }
 }
 
@@ -106,6 +184,7 @@ pub fn spawn_boss_arena(
     mut commands: Commands,
     mut story_flags: ResMut<StoryFlags>,
     mut initialized: ResMut<BossFightInitialized>,
+    loaded_boss: Res<LoadedBossData>,
 ) {
     if initialized.0 {
         info!("Boss fight already initialized, skipping arena spawn");
@@ -117,16 +196,29 @@ pub fn spawn_boss_arena(
 
     // Initialize boss_phase to 1 at start of fight
     story_flags.set("boss_phase", FlagValue::Number(1));
-    let arena_width = 300.0;
-    let arena_height = 200.0;
-    let arena_x = 0.0;
-    let arena_y = -100.0; // Lower part of screen
+
+    spawn_arena_entities(&mut commands, &loaded_boss);
+}
+
+/// Put the arena, its walls and the boss back without restarting the fight (after a hot reload)
+pub fn respawn_boss_arena(mut commands: Commands, loaded_boss: Res<LoadedBossData>) {
+    spawn_arena_entities(&mut commands, &loaded_boss);
+}
+
+fn spawn_arena_entities(commands: &mut Commands, loaded_boss: &LoadedBossData) {
+    let boss = loaded_boss.0.clone().unwrap_or_else(|| {
+        warn!("No boss data for this level, using default boss");
+        BossData::default()
+    });
+    let (arena_width, arena_height) = boss.arena.size;
+    let (arena_x, arena_y) = boss.arena.position;
 
     // Spawn arena data (for collision bounds)
     commands.spawn((
         PlayerArena { width: arena_width, height: arena_height },
         Transform::from_translation(Vec3::new(arena_x, arena_y, 0.0)),
         LevelEntity,
+        Tagged(ARENA_TAG.to_string()),
     ));
 
     // Calculate arena bounds
@@ -136,32 +228,38 @@ pub fn spawn_boss_arena(
     let bottom = arena_y - arena_height / 2.0;
 
     // Spawn arena walls
+    let mut spawn_arena_wall = |pos: Vec3| {
+        let wall = spawn_wall_at(commands, pos, WALL_SIZE);
+        commands.entity(wall).insert(Tagged(ARENA_TAG.to_string()));
+    };
     let cols = (arena_width / WALL_SIZE).ceil() as i32;
     let rows = (arena_height / WALL_SIZE).ceil() as i32;
 
     // Top and bottom walls
     for i in 0..=(cols-1) {
         let x = left + i as f32 * WALL_SIZE;
-        spawn_wall_at(&mut commands, Vec3::new(x, top, 0.0), WALL_SIZE);
-        spawn_wall_at(&mut commands, Vec3::new(x, bottom, 0.0), WALL_SIZE);
+        spawn_arena_wall(Vec3::new(x, top, 0.0));
+        spawn_arena_wall(Vec3::new(x, bottom, 0.0));
     }
 
     // Left and right walls
     for i in 1..rows {
         let y = bottom + i as f32 * WALL_SIZE;
-        spawn_wall_at(&mut commands, Vec3::new(left, y, 0.0), WALL_SIZE);
-        spawn_wall_at(&mut commands, Vec3::new(right, y, 0.0), WALL_SIZE);
+        spawn_arena_wall(Vec3::new(left, y, 0.0));
+        spawn_arena_wall(Vec3::new(right, y, 0.0));
     }
 
     // Spawn boss art above arena
+    let (r, g, b) = boss.color;
     commands.spawn((
-        Text2d::new(SCARY_DOOR_ART),
-        TextFont { font_size: 16.0, ..default() },
-        TextColor(Color::srgb(0.8, 0.2, 0.2)),
-        Transform::from_translation(Vec3::new(arena_x, top + 200.0, 1.0)),
+        Text2d::new(boss.art.clone()),
+        TextFont { font_size: boss.font_size, ..default() },
+        TextColor(Color::srgb(r, g, b)),
+        Transform::from_translation(Vec3::new(arena_x, top + boss.art_height, 1.0)),
         Boss,
         LevelEntity,
     ));
+    info!("Spawned boss '{}'", boss.name);
 }
 
 pub fn reset_boss_fight_initialized(mut initialized: ResMut<BossFightInitialized>) {
@@ -1,8 +1,12 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use crate::level::LoadedLevelData;
+use crate::level_event::LevelEvent;
 use crate::story_flags::StoryFlags;
-use crate::reaction::QueuedDialogue;
-use crate::level_schema::DialogueLine;
+use crate::reaction::{ActionRunner, QueuedDialogue};
+use crate::level_schema::{DialogueChoice, DialogueLine, END_LABEL};
+
+const CHOICE_KEYS: [KeyCode; 4] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4];
 
 #[derive(Component)]
 pub struct DialoguePanel;
@@ -13,10 +17,103 @@ pub struct DialogueSpeakerText;
 #[derive(Component)]
 pub struct DialogueBodyText;
 
+#[derive(Component)]
+pub struct DialogueChoicesText;
+
+/// Text marked `T`, kept apart from the panel's other two texts
+type PanelText<'w, 's, T, A, B> = Query<'w, 's, &'static mut Text, (With<T>, Without<A>, Without<B>)>;
+
+/// The three texts of the open dialogue panel
+#[derive(SystemParam)]
+pub struct DialogueTexts<'w, 's> {
+    speaker: PanelText<'w, 's, DialogueSpeakerText, DialogueBodyText, DialogueChoicesText>,
+    body: PanelText<'w, 's, DialogueBodyText, DialogueSpeakerText, DialogueChoicesText>,
+    choices: PanelText<'w, 's, DialogueChoicesText, DialogueSpeakerText, DialogueBodyText>,
+}
+
+impl DialogueTexts<'_, '_> {
+    fn show(&mut self, speaker: &str, body: &str, choices: &str) {
+        for mut text in self.speaker.iter_mut() {
+            **text = speaker.to_string();
+        }
+        for mut text in self.body.iter_mut() {
+            **text = body.to_string();
+        }
+        self.show_choices(choices);
+    }
+
+    fn show_choices(&mut self, choices: &str) {
+        for mut text in self.choices.iter_mut() {
+            **text = choices.to_string();
+        }
+    }
+}
+
 // Tracks which line of dialogue we're on
 #[derive(Resource, Default)]
 pub struct DialogueState {
     pub current_line: usize,
+    pub selected_choice: usize,
+}
+
+// Get dialogue lines - check QueuedDialogue first, fall back to LoadedLevelData
+fn current_dialogue_lines(queued_dialogue: &QueuedDialogue, loaded_data: &LoadedLevelData) -> Option<Vec<DialogueLine>> {
+    if !queued_dialogue.is_empty() {
+        Some(queued_dialogue.lines.clone())
+    } else {
+        loaded_data.0.as_ref().map(|level_data| level_data.dialogue.clone())
+    }
+}
+
+/// Choices on a line that the player is allowed to see
+fn visible_choices<'a>(line: &'a DialogueLine, story_flags: &StoryFlags) -> Vec<&'a DialogueChoice> {
+    line.choices
+        .iter()
+        .filter(|choice| story_flags.check(&choice.condition))
+        .collect()
+}
+
+fn format_choices(choices: &[&DialogueChoice], selected: usize) -> String {
+    choices
+        .iter()
+        .enumerate()
+        .map(|(i, choice)| {
+            let cursor = if i == selected { ">" } else { " " };
+            format!("{} {}. {}", cursor, i + 1, choice.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Where to continue after a `goto`. None means the dialogue is over.
+fn resolve_goto(lines: &[DialogueLine], current: usize, goto: Option<&str>) -> Option<usize> {
+    match goto {
+        None => Some(current + 1),
+        Some(END_LABEL) => None,
+        Some(label) => {
+            let index = lines.iter().position(|line| line.id.as_deref() == Some(label));
+            if index.is_none() {
+                warn!("Dialogue goto target '{}' not found, ending dialogue", label);
+            }
+            index
+        }
+    }
+}
+
+/// Move to the first line that can be spoken and return its (speaker, text, choices),
+/// or placeholders if there is none
+fn first_line_texts(lines: &[DialogueLine], dialogue_state: &mut DialogueState, story_flags: &StoryFlags) -> (String, String, String) {
+    loop {
+        let Some(line) = lines.get(dialogue_state.current_line) else {
+            return (String::new(), String::new(), String::new());
+        };
+        if story_flags.can_show_line(line) {
+            let choices = format_choices(&visible_choices(line, story_flags), dialogue_state.selected_choice);
+            return (line.speaker.clone(), line.text.clone(), choices);
+        }
+        info!("Skipping initial dialogue from '{}' (not present or condition not met)", line.speaker);
+        dialogue_state.current_line += 1;
+    }
 }
 
 pub fn spawn_dialogue_panel(
@@ -26,30 +123,8 @@ pub fn spawn_dialogue_panel(
     mut dialogue_state: ResMut<DialogueState>,
     queued_dialogue: Res<QueuedDialogue>,
 ) {
-    // Get dialogue lines - check QueuedDialogue first, fall back to LoadedLevelData
-    let dialogue_lines: Vec<DialogueLine> = if !queued_dialogue.is_empty() {
-        queued_dialogue.lines.clone()
-    } else if let Some(level_data) = &loaded_data.0 {
-        level_data.dialogue.clone()
-    } else {
-        Vec::new()
-    };
-
-    // Get first dialogue line that can be spoken, or placeholder if none
-    let (speaker, text) = {
-        // Find first line from a speaker who can speak
-        loop {
-            if dialogue_state.current_line >= dialogue_lines.len() {
-                break ("".to_string(), "".to_string());
-            }
-            let line = &dialogue_lines[dialogue_state.current_line];
-            if story_flags.can_speaker_speak(&line.speaker) {
-                break (line.speaker.clone(), line.text.clone());
-            }
-            info!("Skipping initial dialogue from '{}' (not present)", line.speaker);
-            dialogue_state.current_line += 1;
-        }
-    };
+    let dialogue_lines = current_dialogue_lines(&queued_dialogue, &loaded_data).unwrap_or_default();
+    let (speaker, text, choices) = first_line_texts(&dialogue_lines, &mut dialogue_state, &story_flags);
 
     commands.spawn((
         DialoguePanel,
@@ -85,6 +160,16 @@ pub fn spawn_dialogue_panel(
             },
             TextColor(Color::WHITE),
         ));
+        // Player choices (empty for normal lines)
+        parent.spawn((
+            DialogueChoicesText,
+            Text::new(choices),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.9, 1.0)),
+        ));
     });
 }
 
@@ -92,94 +177,143 @@ pub fn advance_dialogue(
     input: Res<ButtonInput<KeyCode>>,
     loaded_data: Res<LoadedLevelData>,
     mut dialogue_state: ResMut<DialogueState>,
-    mut speaker_query: Query<&mut Text, (With<DialogueSpeakerText>, Without<DialogueBodyText>)>,
-    mut body_query: Query<&mut Text, (With<DialogueBodyText>, Without<DialogueSpeakerText>)>,
-    mut next_state: ResMut<NextState<crate::state::GameState>>,
-    story_flags: Res<StoryFlags>,
-    queued_dialogue: Res<QueuedDialogue>,
+    mut texts: DialogueTexts,
+    mut runner: ActionRunner,
+    mut level_events: MessageWriter<LevelEvent>,
 ) {
-    if !input.just_pressed(KeyCode::Space) && !input.just_pressed(KeyCode::Enter) {
+    let confirm = input.just_pressed(KeyCode::Space) || input.just_pressed(KeyCode::Enter);
+    // Most frames nothing is pressed, so don't even copy the lines
+    let choosing = input.any_just_pressed([KeyCode::ArrowUp, KeyCode::ArrowDown]) || input.any_just_pressed(CHOICE_KEYS);
+    if !confirm && !choosing {
         return;
     }
+    let Some(dialogue_lines) = current_dialogue_lines(&runner.queued_dialogue, &loaded_data) else { return };
 
-    // Get dialogue lines - check QueuedDialogue first, fall back to LoadedLevelData
-    let dialogue_lines: Vec<DialogueLine> = if !queued_dialogue.is_empty() {
-        queued_dialogue.lines.clone()
-    } else if let Some(level_data) = &loaded_data.0 {
-        level_data.dialogue.clone()
-    } else {
-        return;
-    };
+    // Figure out where to go next: a picked choice or the line's own goto
+    let goto = match dialogue_lines.get(dialogue_state.current_line) {
+        Some(line) => {
+            let choices = visible_choices(line, &runner.story_flags);
+            if choices.is_empty() {
+                if !confirm {
+                    return;
+                }
+                line.goto.clone()
+            } else {
+                // Arrow keys move the cursor
+                let count = choices.len();
+                let mut selected = dialogue_state.selected_choice.min(count - 1);
+                if input.just_pressed(KeyCode::ArrowUp) {
+                    selected = (selected + count - 1) % count;
+                }
+                if input.just_pressed(KeyCode::ArrowDown) {
+                    selected = (selected + 1) % count;
+                }
+                if selected != dialogue_state.selected_choice {
+                    dialogue_state.selected_choice = selected;
+                    texts.show_choices(&format_choices(&choices, selected));
+                }
+
+                // Number keys pick directly, Space/Enter picks the highlighted choice
+                let picked = CHOICE_KEYS[..count.min(CHOICE_KEYS.len())]
+                    .iter()
+                    .position(|key| input.just_pressed(*key))
+                    .or(confirm.then_some(selected));
+                let Some(picked) = picked else { return };
 
-    // Determine next state based on queued dialogue or level data
-    let get_next_state = || -> crate::state::GameState {
-        if !queued_dialogue.is_empty() {
-            // Use the then_state from queued dialogue
-            match queued_dialogue.then_state.as_str() {
-                "Playing" => crate::state::GameState::Playing,
-                "BossFight" => crate::state::GameState::BossFight,
-                "LoadingNewLevel" => crate::state::GameState::LoadingNewLevel,
-                "Dialogue" => crate::state::GameState::Dialogue,
-                _ => {
-                    warn!("Unknown then_state: {}, defaulting to Playing", queued_dialogue.then_state);
-                    crate::state::GameState::Playing
+                let choice = choices[picked].clone();
+                info!("Dialogue choice picked: {}", choice.text);
+                runner.run(&choice.actions);
+                if runner.queued_dialogue.restart {
+                    // The choice queued new lines; restart_queued_dialogue takes it from here
+                    return;
                 }
+                choice.goto
             }
-        } else if let Some(level_data) = &loaded_data.0 {
-            if level_data.room_type == "boss" {
-                crate::state::GameState::BossFight
-            } else {
-                crate::state::GameState::Playing
+        }
+        None => {
+            if !confirm {
+                return;
             }
-        } else {
-            crate::state::GameState::Playing
+            None
         }
     };
 
-    dialogue_state.current_line += 1;
+    dialogue_state.selected_choice = 0;
+    let mut next_line = resolve_goto(&dialogue_lines, dialogue_state.current_line, goto.as_deref());
 
-    // Check if we've exhausted all dialogue
-    if dialogue_state.current_line >= dialogue_lines.len() {
-        let target_state = get_next_state();
-        info!("Dialogue finished, transitioning to {:?}", target_state);
-        next_state.set(target_state);
-        return;
+    // Skip dialogue from NPCs that can't speak (dead, traded, not present) or whose condition fails
+    while let Some(index) = next_line {
+        let Some(line) = dialogue_lines.get(index) else {
+            next_line = None;
+            break;
+        };
+        if runner.story_flags.can_show_line(line) {
+            break; // This line can be shown
+        }
+        // Speaker can't speak or condition failed, skip to next line
+        info!("Skipping dialogue from '{}' (not present or condition not met)", line.speaker);
+        next_line = Some(index + 1);
     }
 
-    // Skip dialogue from NPCs that can't speak (dead, traded, not present)
-    loop {
-        // Check if we've exhausted all dialogue while skipping
-        if dialogue_state.current_line >= dialogue_lines.len() {
-            let target_state = get_next_state();
-            info!("Dialogue finished (after skipping), transitioning to {:?}", target_state);
-            next_state.set(target_state);
-            return;
+    // Check if we've exhausted all dialogue
+    let Some(index) = next_line else {
+        level_events.write(LevelEvent::DialogueComplete);
+        // A choice may already have sent us somewhere (e.g. TransitionToLevel)
+        if matches!(*runner.next_state, NextState::Unchanged) {
+            let target_state = dialogue_then_state(&runner.queued_dialogue, &loaded_data);
+            info!("Dialogue finished, transitioning to {:?}", target_state);
+            runner.next_state.set(target_state);
         }
+        return;
+    };
+    dialogue_state.current_line = index;
 
-        let line = &dialogue_lines[dialogue_state.current_line];
-
-        if story_flags.can_speaker_speak(&line.speaker) {
-            break; // This speaker can speak, show their line
-        }
+    // Update the text to show the valid line
+    let line = &dialogue_lines[index];
+    let choices = format_choices(&visible_choices(line, &runner.story_flags), 0);
+    texts.show(&line.speaker, &line.text, &choices);
+}
 
-        // Speaker can't speak, skip to next line
-        info!("Skipping dialogue from '{}' (not present)", line.speaker);
-        dialogue_state.current_line += 1;
+/// Start lines queued during a dialogue (by a choice or a reaction) in the open panel
+pub fn restart_queued_dialogue(
+    story_flags: Res<StoryFlags>,
+    mut queued_dialogue: ResMut<QueuedDialogue>,
+    mut dialogue_state: ResMut<DialogueState>,
+    mut texts: DialogueTexts,
+) {
+    if !queued_dialogue.restart {
+        return;
     }
+    queued_dialogue.restart = false;
+    info!("Restarting dialogue with {} queued lines", queued_dialogue.lines.len());
 
-    // Update the text to show the valid line
-    let line = &dialogue_lines[dialogue_state.current_line];
+    *dialogue_state = DialogueState::default();
+    let (speaker, text, choices) = first_line_texts(&queued_dialogue.lines, &mut dialogue_state, &story_flags);
+    texts.show(&speaker, &text, &choices);
+}
 
-    for mut text in speaker_query.iter_mut() {
-        **text = line.speaker.clone();
-    }
-    for mut text in body_query.iter_mut() {
-        **text = line.text.clone();
+// Determine next state based on queued dialogue or level data
+fn dialogue_then_state(queued_dialogue: &QueuedDialogue, loaded_data: &LoadedLevelData) -> crate::state::GameState {
+    if !queued_dialogue.is_empty() {
+        // Use the then_state from queued dialogue
+        crate::state::GameState::from_name(&queued_dialogue.then_state).unwrap_or_else(|| {
+            warn!("Unknown then_state: {}, defaulting to Playing", queued_dialogue.then_state);
+            crate::state::GameState::Playing
+        })
+    } else if let Some(level_data) = &loaded_data.0 {
+        if level_data.room_type == "boss" {
+            crate::state::GameState::BossFight
+        } else {
+            crate::state::GameState::Playing
+        }
+    } else {
+        crate::state::GameState::Playing
     }
 }
 
 pub fn reset_dialogue_state(mut dialogue_state: ResMut<DialogueState>) {
     dialogue_state.current_line = 0;
+    dialogue_state.selected_choice = 0;
 }
 
 pub fn despawn_dialogue_panel(
@@ -198,4 +332,4 @@ pub fn clear_queued_dialogue(mut queued_dialogue: ResMut<QueuedDialogue>) {
         info!("Clearing queued dialogue");
         queued_dialogue.clear();
     }
-}
\ No newline at end of file
+}
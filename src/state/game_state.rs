@@ -4,10 +4,26 @@ use bevy::prelude::*;
 pub enum GameState {
     #[default]
     Loading,
+    Title,
     Playing,
     BossFight,
     LoadingNewLevel,
     Dialogue,
     Paused,
-    Defeat
-}
\ No newline at end of file
+    Defeat,
+    /// A level file was missing or broken
+    LoadError,
+}
+
+impl GameState {
+    /// States a level file can send the game to (`QueueDialogue`'s `then`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Playing" => Some(GameState::Playing),
+            "BossFight" => Some(GameState::BossFight),
+            "LoadingNewLevel" => Some(GameState::LoadingNewLevel),
+            "Dialogue" => Some(GameState::Dialogue),
+            _ => None,
+        }
+    }
+}
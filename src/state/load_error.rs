@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use crate::level::{CurrentLevel, RunHistory, START_LEVEL};
+use super::GameState;
+
+/// Why the last level failed to load, shown on the error screen
+#[derive(Resource)]
+pub struct LevelLoadError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Component)]
+pub struct LoadErrorScreen;
+
+#[derive(Component)]
+pub struct LoadErrorText;
+
+/// Rooms the player can retreat to, and which one is highlighted
+#[derive(Resource)]
+pub struct LoadErrorMenu {
+    selected: usize,
+    options: Vec<(String, String)>, // (label, level id)
+}
+
+fn screen_text(error: &LevelLoadError, menu: &LoadErrorMenu) -> String {
+    let mut text = format!("Couldn't load room\n\n{}\n\n{}\n\n", error.path, error.message);
+    for (i, (label, _)) in menu.options.iter().enumerate() {
+        let cursor = if i == menu.selected { ">" } else { " " };
+        text.push_str(&format!("{} {}\n", cursor, label));
+    }
+    text
+}
+
+pub fn spawn_load_error_screen(
+    mut commands: Commands,
+    error: Res<LevelLoadError>,
+    history: Res<RunHistory>,
+) {
+    // The failed level never made it into the history, so the last entry is where we came from
+    let mut options = Vec::new();
+    if let Some(previous) = history.visited.last() {
+        options.push((format!("Back to {}", previous), previous.clone()));
+    }
+    if history.visited.last().map(String::as_str) != Some(START_LEVEL) {
+        options.push(("Return to the opening room".to_string(), START_LEVEL.to_string()));
+    }
+    let menu = LoadErrorMenu { selected: 0, options };
+
+    commands.spawn((
+        LoadErrorScreen,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(40.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.15, 0.05, 0.05)),
+    )).with_children(|parent| {
+        parent.spawn((
+            LoadErrorText,
+            Text::new(screen_text(&error, &menu)),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+
+    commands.insert_resource(menu);
+    warn!("Level load error screen: {} ({})", error.path, error.message);
+}
+
+pub fn handle_load_error_input(
+    input: Res<ButtonInput<KeyCode>>,
+    error: Res<LevelLoadError>,
+    mut menu: ResMut<LoadErrorMenu>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut text_query: Query<&mut Text, With<LoadErrorText>>,
+) {
+    let count = menu.options.len();
+    if input.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if input.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    if menu.is_changed() {
+        for mut text in text_query.iter_mut() {
+            **text = screen_text(&error, &menu);
+        }
+    }
+
+    if input.just_pressed(KeyCode::Enter) || input.just_pressed(KeyCode::Space) {
+        let (_, level_id) = &menu.options[menu.selected];
+        info!("Leaving load error screen for {}", level_id);
+        current_level.level_id = level_id.clone();
+        current_level.loaded = false;
+        next_state.set(GameState::LoadingNewLevel);
+    }
+}
+
+pub fn despawn_load_error_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<LoadErrorScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<LoadErrorMenu>();
+    commands.remove_resource::<LevelLoadError>();
+}
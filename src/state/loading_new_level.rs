@@ -1,11 +1,19 @@
 use bevy::prelude::*;
+use bevy::asset::LoadState;
 use crate::audio::{CurrentMusic, play_music, stop_music};
 use crate::level_entity::LevelEntity;
-use crate::level::{CurrentLevel, LoadedLevelData, spawn_level_from_data_internal};
+use crate::level::{CurrentLevel, LoadedLevelData, RunHistory, spawn_level_from_data_internal};
+use crate::level_event::LevelEvent;
 use crate::level_schema::LevelData;
+use crate::boss_schema::BossData;
 use crate::story_flags::StoryFlags;
+use crate::item::{CollectedItems, ItemRegistry};
+use crate::cave::roll_cave_seed;
+use crate::run::{CurrentRun, enter_run_room, level_file};
 use super::GameState;
+use super::boss_fight::{CurrentBoss, LoadedBossData};
 use super::loading::{LoadingScreen, LoadingText};
+use super::load_error::LevelLoadError;
 
 pub fn spawn_loading_new_level_screen(mut commands: Commands){
     commands.spawn((
@@ -53,15 +61,59 @@ pub fn despawn_level_entities(
 // Start loading the next level asset
 pub fn start_loading_next_level(
     mut current_level: ResMut<CurrentLevel>,
+    mut current_boss: ResMut<CurrentBoss>,
     asset_server: Res<AssetServer>,
 ) {
-    let path = format!("levels/{}.ron", current_level.level_id);
+    // Run rooms load their template's file
+    let path = format!("levels/{}.ron", level_file(&current_level.level_id));
     current_level.handle = asset_server.load(&path);
     current_level.loaded = false;
+    *current_boss = CurrentBoss::default();
     info!("Loading next level: {}", path);
 }
 
+// Once the level is loaded, load its boss file too (LevelData.boss)
+pub fn load_level_boss(
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<LevelData>>,
+    boss_assets: Res<Assets<BossData>>,
+    asset_server: Res<AssetServer>,
+    mut current_boss: ResMut<CurrentBoss>,
+    mut loaded_boss: ResMut<LoadedBossData>,
+) {
+    if current_boss.ready {
+        return;
+    }
+    let Some(level_data) = level_assets.get(&current_level.handle) else { return };
+
+    let Some(boss_id) = &level_data.boss else {
+        loaded_boss.0 = None;
+        current_boss.ready = true;
+        return;
+    };
+
+    let handle = current_boss.handle
+        .get_or_insert_with(|| {
+            let path = format!("bosses/{}.boss.ron", boss_id);
+            info!("Loading boss: {}", path);
+            asset_server.load(path)
+        })
+        .clone();
+
+    if let Some(boss_data) = boss_assets.get(&handle) {
+        info!("Boss loaded: {}", boss_data.name);
+        loaded_boss.0 = Some(boss_data.clone());
+        current_boss.ready = true;
+    } else if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&handle) {
+        // Don't block the level on a broken boss file, fall back to the default boss
+        warn!("Failed to load boss '{}': {}", boss_id, err);
+        loaded_boss.0 = None;
+        current_boss.ready = true;
+    }
+}
+
 // Check if level asset is loaded, spawn it, then transition
+#[allow(clippy::too_many_arguments)]
 pub fn check_new_level_ready(
     mut commands: Commands,
     mut current_level: ResMut<CurrentLevel>,
@@ -69,21 +121,55 @@ pub fn check_new_level_ready(
     mut loaded_data: ResMut<LoadedLevelData>,
     mut next_state: ResMut<NextState<GameState>>,
     windows: Query<&Window>,
-    story_flags: Res<StoryFlags>,
+    mut story_flags: ResMut<StoryFlags>,
+    item_registry: Res<ItemRegistry>,
+    collected_items: Res<CollectedItems>,
+    mut current_run: ResMut<CurrentRun>,
     asset_server: Res<AssetServer>,
     mut current_music: ResMut<CurrentMusic>,
+    mut history: ResMut<RunHistory>,
+    current_boss: Res<CurrentBoss>,
+    mut level_events: MessageWriter<LevelEvent>,
 ) {
     if current_level.loaded {
         return;
     }
 
+    // A missing or broken level file never shows up in level_assets, so don't wait on it forever
+    if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&current_level.handle) {
+        let path = format!("levels/{}.ron", level_file(&current_level.level_id));
+        warn!("Failed to load level {}: {}", path, err);
+        commands.insert_resource(LevelLoadError { path, message: err.to_string() });
+        next_state.set(GameState::LoadError);
+        return;
+    }
+
+    // Boss levels wait for their boss file as well
+    if !current_boss.ready {
+        return;
+    }
+
     if let Some(level_data) = level_assets.get(&current_level.handle) {
         info!("Next level loaded: {}", level_data.name);
+        let mut level_data = level_data.clone();
+        enter_run_room(&mut current_run, &current_level.level_id, &mut level_data, &mut story_flags);
+        roll_cave_seed(&mut level_data);
         loaded_data.0 = Some(level_data.clone());
         current_level.loaded = true;
+        history.visited.push(current_level.level_id.clone());
 
         // Spawn the level entities
-        spawn_level_from_data_internal(&mut commands, level_data, &windows, &story_flags);
+        spawn_level_from_data_internal(
+            &mut commands,
+            &level_data,
+            &current_level.level_id,
+            &windows,
+            &story_flags,
+            &item_registry,
+            &collected_items,
+        );
+        // Handled once the room's first state (Dialogue, Playing or BossFight) starts
+        level_events.write(LevelEvent::LevelEntered);
 
         // Handle level music
         match &level_data.music {
@@ -91,8 +177,8 @@ pub fn check_new_level_ready(
             None => stop_music(&mut commands, &mut current_music),
         }
 
-        // Transition based on dialogue and level type
-        if !level_data.dialogue.is_empty() {
+        // Transition based on dialogue and level type (skip dialogue if no line can be shown)
+        if level_data.dialogue.iter().any(|line| story_flags.can_show_line(line)) {
             next_state.set(GameState::Dialogue);
         } else if level_data.room_type == "boss" {
             next_state.set(GameState::BossFight);
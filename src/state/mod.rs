@@ -10,26 +10,41 @@ mod loading_new_level;
 mod dialogue;
 pub mod boss_fight;
 mod defeat;
+mod title;
+pub mod load_error;
 
 pub use game_state::GameState;
 
 use crate::player::PlayerHealth;
 use crate::ui;
 use crate::story_flags::{StoryFlags, FlagValue};
+use crate::inventory::Inventory;
 use crate::level_event::LevelEvent;
-use crate::reaction::{process_reactions, QueuedDialogue};
+use crate::reaction::{process_reactions, run_scheduled_actions, FiredReactions, QueuedDialogue, ScheduledActions};
+use crate::save::{SaveDir, SaveSlot, autosave};
+use crate::room_effect::{ActiveEffects, apply_room_effects};
+use crate::item::{CollectedItems, ItemRegistry};
+use crate::player::PlayerModifiers;
+use crate::run::{CurrentRun, restart_run};
 
 pub struct StatePlugin;
 
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
         app
-            .insert_resource(PlayerHealth {current: 3, max: 3})
+            .init_resource::<PlayerHealth>()
             .insert_resource(LoadingTimer(Timer::from_seconds(1.0, TimerMode::Once)))
             .init_resource::<dialogue::DialogueState>()
             .init_resource::<StoryFlags>()
+            .init_resource::<Inventory>()
+            .init_resource::<SaveSlot>()
+            .init_resource::<SaveDir>()
             .init_resource::<QueuedDialogue>()
+            .init_resource::<FiredReactions>()
+            .init_resource::<ScheduledActions>()
             .init_resource::<boss_fight::BossFightInitialized>()
+            .init_resource::<boss_fight::CurrentBoss>()
+            .init_resource::<boss_fight::LoadedBossData>()
             .init_resource::<boss_fight::AttackTimer>()
             .add_message::<LevelEvent>()
             .init_state::<GameState>()
@@ -46,6 +61,11 @@ impl Plugin for StatePlugin {
             .add_systems(OnExit(GameState::Loading),
                 loading::despawn_loading_screen)
 
+            // Title state systems (new game / continue)
+            .add_systems(OnEnter(GameState::Title), title::spawn_title_menu)
+            .add_systems(Update, title::handle_title_input.run_if(in_state(GameState::Title)))
+            .add_systems(OnExit(GameState::Title), title::despawn_title_menu)
+
             // Dialogue state systems (reset must run before spawn_dialogue_panel)
             .add_systems(OnEnter(GameState::Dialogue), (
                 dialogue::reset_dialogue_state,
@@ -64,12 +84,23 @@ impl Plugin for StatePlugin {
                 loading_new_level::despawn_level_entities,
                 loading_new_level::start_loading_next_level,
                 boss_fight::reset_boss_fight_initialized,
+                clear_scheduled_actions,
             ))
             .add_systems(Update, (
                 loading_new_level::animate_loading_room,
-                loading_new_level::check_new_level_ready,
+                (loading_new_level::load_level_boss, loading_new_level::check_new_level_ready).chain(),
             ).run_if(in_state(GameState::LoadingNewLevel)))
             .add_systems(OnExit(GameState::LoadingNewLevel), loading::despawn_loading_screen)
+            // Only a room that loaded becomes the Continue point. Saved before its on_enter
+            // effects, which apply again when the save is continued.
+            .add_systems(OnExit(GameState::LoadingNewLevel), autosave
+                .run_if(|current_level: Res<crate::level::CurrentLevel>| current_level.loaded)
+                .before(apply_room_effects))
+
+            // LoadError state systems (level file missing or broken)
+            .add_systems(OnEnter(GameState::LoadError), load_error::spawn_load_error_screen)
+            .add_systems(Update, load_error::handle_load_error_input.run_if(in_state(GameState::LoadError)))
+            .add_systems(OnExit(GameState::LoadError), load_error::despawn_load_error_screen)
 
             // Pause state systems
             .add_systems(OnEnter(GameState::Paused), pause::spawn_pause_menu)
@@ -96,7 +127,21 @@ impl Plugin for StatePlugin {
                 ui::spawn_follower_health_ui,
             ))
             .add_systems(Update, boss_fight::fire_projectiles_at_player.run_if(in_state(GameState::BossFight)))
-            .add_systems(Update, process_reactions.run_if(in_state(GameState::BossFight)))
+
+            // Level reactions. PostUpdate, so events sent this frame (e.g. DoorOpened) are
+            // handled in the room they happened in, before any state change takes effect.
+            .add_systems(PostUpdate, process_reactions.run_if(
+                in_state(GameState::Playing)
+                    .or(in_state(GameState::Dialogue))
+                    .or(in_state(GameState::BossFight))
+            ))
+            // Lines queued during a dialogue take over its panel, whoever queued them
+            .add_systems(PostUpdate, dialogue::restart_queued_dialogue.after(process_reactions)
+                .run_if(in_state(GameState::Dialogue)))
+            // Wait/After/Repeat timers; they hold still during dialogue and the pause menu
+            .add_systems(PostUpdate, run_scheduled_actions.after(process_reactions).run_if(
+                in_state(GameState::Playing).or(in_state(GameState::BossFight))
+            ))
 
             // Health UI systems
             .add_systems(Update, ui::update_health_ui.run_if(resource_changed::<PlayerHealth>))
@@ -114,10 +159,15 @@ fn check_assets_loaded(
     timer.0.tick(time.delta());
 
     if timer.0.is_finished() {
-        next_state.set(GameState::LoadingNewLevel);
+        next_state.set(GameState::Title);
     }
 }
 
+// Sequences from the last room don't follow the player into the next one
+fn clear_scheduled_actions(mut scheduled: ResMut<ScheduledActions>) {
+    scheduled.clear();
+}
+
 fn init_story_flags(mut flags: ResMut<StoryFlags>) {
     flags.set("duck_status", FlagValue::Text("alive".to_string()));
     flags.set("duck_present", FlagValue::Bool(true));
@@ -126,6 +176,7 @@ fn init_story_flags(mut flags: ResMut<StoryFlags>) {
     info!("Story flags initialized");
 }
 
+#[allow(clippy::too_many_arguments)]
 fn toggle_pause( // ALSO HANDLES RESTARTING GAME TODO: DONT PUT RESTART LOGIC IN HERE
     input: Res<ButtonInput<KeyCode>>,
     current_state: Res<State<GameState>>,
@@ -133,6 +184,13 @@ fn toggle_pause( // ALSO HANDLES RESTARTING GAME TODO: DONT PUT RESTART LOGIC IN
     mut player_health: ResMut<PlayerHealth>,
     mut current_level: ResMut<crate::level::CurrentLevel>,
     mut story_flags: ResMut<StoryFlags>,
+    mut active_effects: ResMut<ActiveEffects>,
+    current_run: Res<CurrentRun>,
+    mut inventory: ResMut<Inventory>,
+    mut modifiers: ResMut<PlayerModifiers>,
+    mut collected_items: ResMut<CollectedItems>,
+    mut fired_reactions: ResMut<FiredReactions>,
+    item_registry: Res<ItemRegistry>,
 ) {
     if input.just_pressed(KeyCode::Escape) {
         match current_state.get() {
@@ -146,15 +204,32 @@ fn toggle_pause( // ALSO HANDLES RESTARTING GAME TODO: DONT PUT RESTART LOGIC IN
             }
             GameState::Defeat => {
                 info!("Game restarted");
-                // Reset player health
-                player_health.current = player_health.max;
+                // Reset player health, dropping what MaxHealth effects did to the max
+                *player_health = PlayerHealth::default();
                 // Reset story flags (duck comes back!)
                 story_flags.set("duck_status", FlagValue::Text("alive".to_string()));
                 story_flags.set("duck_present", FlagValue::Bool(true));
                 story_flags.set("duck_health", FlagValue::Number(3));
-                // Reset to first level
-                current_level.level_id = "level_00_opening".to_string();
-                current_level.loaded = false;
+                // Buffs and curses end with the run
+                active_effects.0.clear();
+                // One-shot reactions can fire again
+                fired_reactions.0.clear();
+                // Nothing picked up in the last attempt carries over
+                *inventory = Inventory::default();
+                *modifiers = PlayerModifiers::default();
+                if let Some(run) = &current_run.0 {
+                    // Dying in a run starts it over
+                    restart_run(run, &mut current_level, &mut story_flags, &mut collected_items);
+                } else {
+                    // Reset to first level, with every item back in its room and the
+                    // flags items wrote (coins, lantern) gone with them
+                    collected_items.0.clear();
+                    for key in item_registry.flag_keys() {
+                        story_flags.remove(key);
+                    }
+                    current_level.level_id = crate::level::START_LEVEL.to_string();
+                    current_level.loaded = false;
+                }
                 // Go to LoadingNewLevel to reload
                 next_state.set(GameState::LoadingNewLevel);
             }
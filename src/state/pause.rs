@@ -1,9 +1,19 @@
 use bevy::prelude::*;
+use crate::level::CurrentLevel;
+use crate::run::{CurrentRun, split_run_room_id};
 
 #[derive(Component)]
 pub struct PauseMenu;
 
-pub fn spawn_pause_menu(mut commands: Commands) {
+pub fn spawn_pause_menu(mut commands: Commands, current_run: Res<CurrentRun>, current_level: Res<CurrentLevel>) {
+    // The seed is what players share to replay (or report) a run
+    let mut text = "PAUSED\n\n".to_string();
+    if let Some(run) = &current_run.0 {
+        let depth = split_run_room_id(&current_level.level_id).map_or(0, |(_, depth)| depth);
+        text.push_str(&format!("Run seed: {}\nRoom {} of {}\n\n", run.seed, depth, run.plan.depth()));
+    }
+    text.push_str("Press ESC to resume");
+
     commands.spawn((
         PauseMenu,
         Node {
@@ -16,7 +26,7 @@ pub fn spawn_pause_menu(mut commands: Commands) {
         BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
     )).with_children(|parent| {
         parent.spawn((
-            Text::new("PAUSED\n\nPress ESC to resume"),
+            Text::new(text),
             TextFont {
                 font_size: 36.0,
                 ..default()
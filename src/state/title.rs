@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use crate::level::CurrentLevel;
+use std::path::Path;
+use crate::run::{CurrentRun, DefaultRun, DEFAULT_RUN, start_run};
+use crate::run_schema::RunData;
+use crate::save::{SaveDir, SaveSlot, SAVE_SLOTS, apply_save, back_up_slot, load_slot, slot_exists};
+use super::GameState;
+
+#[derive(Component)]
+pub struct TitleMenu;
+
+#[derive(Component)]
+pub struct TitleMenuText;
+
+enum SlotStatus {
+    Empty,
+    Saved { level_id: String, rooms: usize, run_seed: Option<u64> },
+    Broken(String),
+}
+
+#[derive(Resource)]
+pub struct TitleMenuState {
+    selected: usize,
+    slots: Vec<SlotStatus>,
+    /// Digits typed for the next run's seed (blank rolls one)
+    seed: String,
+    /// Shown under the slots, e.g. why Enter did nothing
+    notice: Option<String>,
+}
+
+const DIGIT_KEYS: [KeyCode; 10] = [
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
+fn read_slots(dir: &Path) -> Vec<SlotStatus> {
+    (1..=SAVE_SLOTS)
+        .map(|slot| {
+            if !slot_exists(dir, slot) {
+                return SlotStatus::Empty;
+            }
+            match load_slot(dir, slot) {
+                Ok(data) => SlotStatus::Saved {
+                    level_id: data.level_id,
+                    rooms: data.history.visited.len(),
+                    run_seed: data.run.map(|run| run.seed),
+                },
+                Err(err) => {
+                    warn!("Save slot {} unusable: {}", slot, err);
+                    SlotStatus::Broken(err.to_string())
+                }
+            }
+        })
+        .collect()
+}
+
+fn menu_text(menu: &TitleMenuState) -> String {
+    let mut text = "DOORS\n\n".to_string();
+    for (i, status) in menu.slots.iter().enumerate() {
+        let cursor = if i == menu.selected { ">" } else { " " };
+        let summary = match status {
+            SlotStatus::Empty => "Empty".to_string(),
+            SlotStatus::Saved { level_id, rooms, run_seed: None } => format!("{} ({} rooms)", level_id, rooms),
+            SlotStatus::Saved { level_id, rooms, run_seed: Some(seed) } => format!("run {} at {} ({} rooms)", seed, level_id, rooms),
+            SlotStatus::Broken(reason) => format!("({})", reason),
+        };
+        text.push_str(&format!("{} Slot {} - {}\n", cursor, i + 1, summary));
+    }
+
+    let hint = match menu.slots.get(menu.selected) {
+        Some(SlotStatus::Saved { .. }) => "Enter: Continue   N: New Game   R: New Run",
+        Some(SlotStatus::Broken(_)) => "N: New Game   R: New Run (the old save is kept as .bak)",
+        _ => "Enter: New Game   R: New Run",
+    };
+    if let Some(notice) = &menu.notice {
+        text.push_str(&format!("\n{}", notice));
+    }
+    text.push_str(&format!("\n{}", hint));
+    let seed = if menu.seed.is_empty() { "random (type digits to pick one)" } else { &menu.seed };
+    text.push_str(&format!("\nRun seed: {}", seed));
+    text
+}
+
+pub fn spawn_title_menu(mut commands: Commands, slot: Res<SaveSlot>, dir: Res<SaveDir>) {
+    let menu = TitleMenuState {
+        selected: (slot.0 as usize).saturating_sub(1),
+        slots: read_slots(&dir.0),
+        seed: String::new(),
+        notice: None,
+    };
+
+    commands.spawn((
+        TitleMenu,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.1, 0.1, 0.15)),
+    )).with_children(|parent| {
+        parent.spawn((
+            TitleMenuText,
+            Text::new(menu_text(&menu)),
+            TextFont {
+                font_size: 32.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+
+    commands.insert_resource(menu);
+    info!("Title menu spawned");
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_title_input(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<TitleMenuState>,
+    mut slot: ResMut<SaveSlot>,
+    dir: Res<SaveDir>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut current_run: ResMut<CurrentRun>,
+    default_run: Res<DefaultRun>,
+    run_assets: Res<Assets<RunData>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut text_query: Query<&mut Text, With<TitleMenuText>>,
+) {
+    let count = menu.slots.len();
+    if input.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + count - 1) % count;
+        menu.notice = None;
+    }
+    if input.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % count;
+        menu.notice = None;
+    }
+    for (digit, key) in DIGIT_KEYS.iter().enumerate() {
+        if !input.just_pressed(*key) {
+            continue;
+        }
+        // Every u64 can be typed back in (rolled seeds run to 20 digits), nothing past that
+        let seed = format!("{}{}", menu.seed, digit);
+        if seed.parse::<u64>().is_ok() {
+            menu.seed = seed;
+        }
+    }
+    if input.just_pressed(KeyCode::Backspace) {
+        menu.seed.pop();
+    }
+    if menu.is_changed() {
+        for mut text in text_query.iter_mut() {
+            **text = menu_text(&menu);
+        }
+    }
+
+    let confirm = input.just_pressed(KeyCode::Enter) || input.just_pressed(KeyCode::Space);
+    let new_game = input.just_pressed(KeyCode::KeyN);
+    let new_run = input.just_pressed(KeyCode::KeyR);
+    if !confirm && !new_game && !new_run {
+        return;
+    }
+
+    slot.0 = menu.selected as u8 + 1;
+
+    let run_data = if new_run {
+        let Some(run_data) = run_assets.get(&default_run.0) else {
+            warn!("Run file isn't loaded (assets/runs/{}.run.ron), can't start a run", DEFAULT_RUN);
+            return;
+        };
+        Some(run_data)
+    } else {
+        None
+    };
+
+    // A save we can't read (newer, unsupported or corrupt) is never overwritten by the
+    // first autosave: Enter refuses, and N/R move it aside first
+    if matches!(menu.slots[menu.selected], SlotStatus::Broken(_)) {
+        if confirm {
+            menu.notice = Some(format!("Slot {} can't be continued. N or R starts over and keeps it as .bak", slot.0));
+            for mut text in text_query.iter_mut() {
+                **text = menu_text(&menu);
+            }
+            return;
+        }
+        match back_up_slot(&dir.0, slot.0) {
+            Ok(backup) => info!("Moved unusable save in slot {} to {}", slot.0, backup.display()),
+            Err(err) => {
+                warn!("Could not move the save in slot {} aside, not starting over: {}", slot.0, err);
+                return;
+            }
+        }
+    }
+
+    if let Some(run_data) = run_data {
+        let seed = menu.seed.parse().unwrap_or_else(|_| rand::random());
+        info!("New run in slot {}", slot.0);
+        start_run(&mut current_run, &mut current_level, run_data, seed);
+        next_state.set(GameState::LoadingNewLevel);
+        return;
+    }
+
+    // Continue from the selected slot if it has a usable save
+    if confirm && matches!(menu.slots[menu.selected], SlotStatus::Saved { .. }) {
+        match load_slot(&dir.0, slot.0) {
+            Ok(data) => {
+                info!("Continuing from slot {}", slot.0);
+                apply_save(&mut commands, &mut current_level, data);
+            }
+            Err(err) => {
+                warn!("Could not continue from slot {}: {}", slot.0, err);
+                return;
+            }
+        }
+    } else {
+        // Fresh resources from startup are the new run; the first autosave overwrites the slot
+        info!("New game in slot {}", slot.0);
+    }
+
+    next_state.set(GameState::LoadingNewLevel);
+}
+
+pub fn despawn_title_menu(
+    mut commands: Commands,
+    query: Query<Entity, With<TitleMenu>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<TitleMenuState>();
+
+    info!("Title menu despawned");
+}
@@ -1,8 +1,9 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::level_schema::{Condition, DialogueLine};
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct StoryFlags {
     flags: HashMap<String, FlagValue>,
 }
@@ -20,6 +21,10 @@ impl StoryFlags {
         self.flags.insert(key.to_string(), value);
     }
 
+    pub fn remove(&mut self, key: &str) {
+        self.flags.remove(key);
+    }
+
     pub fn get(&self, key: &str) -> Option<&FlagValue> {
         self.flags.get(key)
     }
@@ -45,10 +50,35 @@ impl StoryFlags {
         }
     }
 
+    /// Every flag, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FlagValue)> {
+        self.flags.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    pub fn check(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Always => true,
+            Condition::Equals(key, value) => self.get(key) == Some(value),
+            Condition::NotEquals(key, value) => self.get(key) != Some(value),
+            Condition::GreaterThan(key, value) => self.get_number(key).is_some_and(|n| n > *value),
+            Condition::AtLeast(key, value) => self.get_number(key).is_some_and(|n| n >= *value),
+            Condition::LessThan(key, value) => self.get_number(key).is_some_and(|n| n < *value),
+            Condition::Has(key) => !matches!(self.get(key), None | Some(FlagValue::Bool(false))),
+            Condition::All(conditions) => conditions.iter().all(|c| self.check(c)),
+            Condition::Any(conditions) => conditions.iter().any(|c| self.check(c)),
+            Condition::Not(condition) => !self.check(condition),
+        }
+    }
+
     /// Check if a speaker can speak (is present).
     /// Returns true if no flag exists (narrators, "---", etc. always speak).
     pub fn can_speaker_speak(&self, speaker: &str) -> bool {
         let present_key = format!("{}_present", speaker.to_lowercase());
         self.get_bool(&present_key).unwrap_or(true)
     }
+
+    /// A dialogue line is shown if its speaker is present and its condition holds
+    pub fn can_show_line(&self, line: &DialogueLine) -> bool {
+        self.can_speaker_speak(&line.speaker) && self.check(&line.condition)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use crate::level::{TILE_SIZE, spawn_floor_at, spawn_wall_at};
+use crate::level_schema::{LevelData, NpcData};
+
+pub const WALL: char = '#';
+pub const FLOOR: char = '.';
+pub const DOOR: char = 'D';
+pub const PLAYER: char = '@';
+pub const DUCK: char = 'd';
+/// Every character a `map` room understands (space is empty, nothing spawns)
+pub const MAP_TILES: &[char] = &[WALL, FLOOR, DOOR, PLAYER, DUCK, ' '];
+
+/// ASCII layout of a `map` room (`LevelData.map`), one tile per character
+pub struct TileMap {
+    rows: Vec<Vec<char>>,
+}
+
+impl TileMap {
+    /// Blank lines around the map and the indentation common to every row are ignored,
+    /// so the map can be indented to match the RON around it
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let first = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(lines.len());
+        let last = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(first, |i| i + 1);
+        let lines = &lines[first..last];
+
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let rows = lines
+            .iter()
+            .map(|line| line.chars().skip(indent).collect::<String>().trim_end().chars().collect())
+            .collect();
+        Self { rows }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Centre of a tile in world space, with the whole map centred on the origin
+    pub fn position(&self, column: usize, row: usize) -> (f32, f32) {
+        (
+            (column as f32 + 0.5 - self.width() as f32 / 2.0) * TILE_SIZE,
+            (self.height() as f32 / 2.0 - row as f32 - 0.5) * TILE_SIZE,
+        )
+    }
+
+    /// Every tile as (column, row, character), left to right then top to bottom
+    pub fn tiles(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, tiles)| tiles.iter().enumerate().map(move |(column, tile)| (column, row, *tile)))
+    }
+
+    /// Positions of every `tile`, in reading order
+    pub fn find(&self, tile: char) -> Vec<(f32, f32)> {
+        self.tiles()
+            .filter(|(_, _, t)| *t == tile)
+            .map(|(column, row, _)| self.position(column, row))
+            .collect()
+    }
+
+    fn replace(&mut self, from: char, to: char) {
+        for tile in self.rows.iter_mut().flatten() {
+            if *tile == from {
+                *tile = to;
+            }
+        }
+    }
+
+    fn to_text(&self) -> String {
+        self.rows.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Move the player start, doors and ducks to their tiles in `level.map`: the nth `D` places the
+/// nth door, `d`s place the `duck` NPCs (spawning more ducks if there are spare `d`s), and `@`
+/// is the player start. The markers are turned into floor, so placing twice changes nothing.
+pub fn place_on_map(level: &mut LevelData) {
+    if level.room_type != "map" {
+        return;
+    }
+    let Some(text) = &level.map else { return };
+    let mut map = TileMap::parse(text);
+
+    if let Some(start) = map.find(PLAYER).first() {
+        level.player_start = *start;
+    }
+    for (door, position) in level.doors.iter_mut().zip(map.find(DOOR)) {
+        door.position = position;
+    }
+    let mut ducks = map.find(DUCK).into_iter();
+    for npc in level.npcs.iter_mut().filter(|npc| npc.name == "duck") {
+        match ducks.next() {
+            Some(position) => npc.position = position,
+            None => break,
+        }
+    }
+    for position in ducks {
+        level.npcs.push(NpcData { name: "duck".to_string(), position, extra: Vec::new() });
+    }
+
+    for marker in [PLAYER, DOOR, DUCK] {
+        map.replace(marker, FLOOR);
+    }
+    level.map = Some(map.to_text());
+}
+
+/// Walls and floor for a `map` room; doors, NPCs and the player are spawned from the level data
+pub fn spawn_map_tiles(commands: &mut Commands, text: &str) {
+    let map = TileMap::parse(text);
+    for (column, row, tile) in map.tiles() {
+        let (x, y) = map.position(column, row);
+        let pos = Vec3::new(x, y, 0.0);
+        match tile {
+            WALL => {
+                spawn_wall_at(commands, pos, TILE_SIZE);
+            }
+            ' ' => {}
+            FLOOR | DOOR | PLAYER | DUCK => spawn_floor_at(commands, pos),
+            _ => {
+                warn!("Unknown map tile '{}' at column {}, row {}", tile, column + 1, row + 1);
+                spawn_floor_at(commands, pos);
+            }
+        }
+    }
+    info!("Spawned {}x{} tile map", map.width(), map.height());
+}
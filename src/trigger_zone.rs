@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+use crate::hitbox::{HitBox, PlayerTouchedSomething, detect_col_with_player};
+use crate::level_entity::LevelEntity;
+use crate::level_event::LevelEvent;
+use crate::level_schema::TriggerZoneData;
+use crate::state::GameState;
+
+/// Invisible box that sends `ZoneEntered`/`ZoneLeft` with its name as the player crosses it
+#[derive(Component)]
+pub struct TriggerZone {
+    pub name: String,
+    /// Remove the zone after it first sends `ZoneEntered`
+    pub once: bool,
+    /// Player was inside last frame
+    pub inside: bool,
+}
+
+pub fn spawn_trigger_zone_from_data(commands: &mut Commands, zone_data: &TriggerZoneData) {
+    commands.spawn((
+        TriggerZone {
+            name: zone_data.name.clone(),
+            once: zone_data.once,
+            inside: false,
+        },
+        Transform::from_translation(Vec3::new(zone_data.position.0, zone_data.position.1, 0.0)),
+        HitBox { width: zone_data.size.0, height: zone_data.size.1 },
+        LevelEntity,
+    ));
+    info!("Spawned trigger zone '{}' at ({}, {})", zone_data.name, zone_data.position.0, zone_data.position.1);
+}
+
+// The player touches a zone every frame they're in it, so compare with last frame
+fn update_trigger_zones(
+    mut commands: Commands,
+    mut messages: MessageReader<PlayerTouchedSomething>,
+    mut zones: Query<(Entity, &mut TriggerZone)>,
+    mut level_events: MessageWriter<LevelEvent>,
+) {
+    let touched: HashSet<Entity> = messages.read().map(|message| message.messaging_entity).collect();
+
+    for (entity, mut zone) in &mut zones {
+        let inside = touched.contains(&entity);
+        if inside == zone.inside {
+            continue;
+        }
+        zone.inside = inside;
+
+        if inside {
+            info!("Player entered zone '{}'", zone.name);
+            level_events.write(LevelEvent::ZoneEntered { name: zone.name.clone() });
+            if zone.once {
+                commands.entity(entity).despawn();
+            }
+        } else {
+            info!("Player left zone '{}'", zone.name);
+            level_events.write(LevelEvent::ZoneLeft { name: zone.name.clone() });
+        }
+    }
+}
+
+pub struct TriggerZonePlugin;
+
+impl Plugin for TriggerZonePlugin {
+    fn build(&self, app: &mut App) {
+        // Zones are spawned by level.rs from RON data
+        app.add_systems(Update, update_trigger_zones.after(detect_col_with_player).run_if(
+            in_state(GameState::Playing).or(in_state(GameState::BossFight))
+        ));
+    }
+}
@@ -4,6 +4,8 @@ use crate::npc::Npc;
 use crate::player::PlayerHealth;
 use crate::art::{FULL_HEART, EMPTY_HEART};
 use crate::story_flags::StoryFlags;
+use crate::level::CurrentLevel;
+use crate::room_effect::ActiveEffects;
 
 #[derive(Component)]
 pub struct HealthContainer;
@@ -30,9 +32,9 @@ pub fn spawn_follower_health_ui(
     followers: Query<&Npc, With<Follow>>,
 ){
     let starting_pos_value = 40.0;
-    let mut num_in_party = 2; // Start after player's row
 
-    for npc in &followers {
+    // Start after player's row
+    for (num_in_party, npc) in (2..).zip(&followers) {
         let name_lower = npc.name.to_lowercase();
         let health = story_flags.get_number(&format!("{}_health", name_lower)).unwrap_or(0);
         let max_health = story_flags.get_number(&format!("{}_max_health", name_lower)).unwrap_or(0);
@@ -66,7 +68,6 @@ pub fn spawn_follower_health_ui(
                 ));
             }
         });
-        num_in_party += 1;
     }
 }
 
@@ -143,4 +144,53 @@ pub fn despawn_health_ui(mut commands: Commands, query: Query<Entity, With<Healt
     for entity in &query {
         commands.entity(entity).despawn();
     }
-}
\ No newline at end of file
+}
+/// Active buffs and curses, top right
+#[derive(Component)]
+pub struct ActiveEffectsText;
+
+fn effects_text(active: &ActiveEffects) -> String {
+    active.0
+        .iter()
+        .map(|effect| {
+            let sign = if effect.curse { "-" } else { "+" };
+            match effect.rooms_left {
+                1 => format!("{} {} (last room)", sign, effect.name),
+                rooms => format!("{} {} ({} rooms)", sign, effect.name, rooms),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn spawn_effects_ui(mut commands: Commands, current_level: Res<CurrentLevel>, active: Res<ActiveEffects>) {
+    // Not over the load error screen
+    if !current_level.loaded {
+        return;
+    }
+    commands.spawn((
+        ActiveEffectsText,
+        Text::new(effects_text(&active)),
+        TextFont { font_size: 16.0, ..default() },
+        TextColor(Color::srgb(0.8, 0.7, 1.0)),
+        TextLayout::new_with_justify(Justify::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.0),
+            right: Val::Px(20.0),
+            ..default()
+        },
+    ));
+}
+
+pub fn update_effects_ui(active: Res<ActiveEffects>, mut texts: Query<&mut Text, With<ActiveEffectsText>>) {
+    for mut text in &mut texts {
+        **text = effects_text(&active);
+    }
+}
+
+pub fn despawn_effects_ui(mut commands: Commands, query: Query<Entity, With<ActiveEffectsText>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
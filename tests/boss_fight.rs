@@ -0,0 +1,69 @@
+mod common;
+
+use bevy_game::player::{GodMode, PlayerHealth};
+use bevy_game::projectile::Projectile;
+use bevy_game::state::GameState;
+use bevy_game::state::boss_fight::LoadedBossData;
+use common::TestGame;
+
+fn boss_phase(game: &TestGame) -> Option<i32> {
+    game.flags().get_number("boss_phase")
+}
+
+#[test]
+fn boss_moves_to_phase_two_after_first_volleys() {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+    assert_eq!(boss_phase(&game), Some(1));
+
+    // Survive long enough to see the whole phase
+    *game.resource_mut::<PlayerHealth>() = PlayerHealth { current: 100, max: 100 };
+
+    game.step_seconds(2.5);
+    assert!(game.count::<Projectile>() > 0, "boss should be firing");
+
+    // Phase 1 ends with a taunt, then the fight resumes in phase 2
+    assert!(game.wait_for_state(GameState::Dialogue));
+    assert_eq!(boss_phase(&game), Some(2));
+    game.skip_dialogue(5);
+    assert_eq!(game.state(), GameState::BossFight);
+}
+
+#[test]
+fn projectiles_hurt_the_player() {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    let start = game.health().current;
+
+    assert!(game.run_until(|game| game.health().current < start));
+}
+
+#[test]
+fn god_mode_takes_no_damage() {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    game.resource_mut::<GodMode>().0 = true;
+    let start = game.health().current;
+
+    game.step_seconds(2.5);
+    assert!(game.count::<Projectile>() > 0, "boss should be firing");
+    assert_eq!(game.health().current, start);
+}
+
+#[test]
+fn bad_boss_timings_fire_instead_of_crashing() {
+    let mut game = TestGame::new();
+    game.load_level("boss_test");
+    {
+        let mut loaded_boss = game.resource_mut::<LoadedBossData>();
+        let phase = &mut loaded_boss.0.as_mut().unwrap().phases[0];
+        phase.interval = -1.0;
+        phase.patterns[0].wait = Some(f32::NAN);
+    }
+    game.skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+
+    game.step(5);
+    assert!(game.count::<Projectile>() > 0);
+}
@@ -0,0 +1,95 @@
+use bevy_game::cave::{CaveLayout, place_in_cave};
+use bevy_game::level_schema::{CaveGenerator, CaveSettings, LevelData};
+
+const GENERATORS: [CaveGenerator; 7] = [
+    CaveGenerator::CellularAutomata,
+    CaveGenerator::BspRooms,
+    CaveGenerator::BspInterior,
+    CaveGenerator::SimpleRooms,
+    CaveGenerator::DrunkardsWalk,
+    CaveGenerator::Maze,
+    CaveGenerator::Voronoi,
+];
+
+fn settings(generator: CaveGenerator) -> CaveSettings {
+    CaveSettings { seed: None, generator, size: (40, 30) }
+}
+
+fn tiles(cave: &CaveLayout) -> Vec<bool> {
+    (0..cave.height).flat_map(|y| (0..cave.width).map(move |x| cave.is_walkable(x, y))).collect()
+}
+
+fn first_floor(cave: &CaveLayout) -> (usize, usize) {
+    let index = tiles(cave).iter().position(|walkable| *walkable).expect("cave has floor");
+    (index % cave.width, index / cave.width)
+}
+
+#[test]
+fn same_seed_same_cave() {
+    for generator in GENERATORS {
+        let cave = CaveLayout::generate(&settings(generator), 42);
+        assert_eq!(tiles(&cave), tiles(&CaveLayout::generate(&settings(generator), 42)), "{:?}", generator);
+    }
+    let a = CaveLayout::generate(&settings(CaveGenerator::CellularAutomata), 1);
+    let b = CaveLayout::generate(&settings(CaveGenerator::CellularAutomata), 2);
+    assert_ne!(tiles(&a), tiles(&b));
+}
+
+#[test]
+fn every_floor_tile_is_reachable_and_the_edge_is_solid() {
+    for generator in GENERATORS {
+        for seed in 0..5 {
+            let cave = CaveLayout::generate(&settings(generator), seed);
+            assert!(cave.walkable_count() > 20, "{:?} seed {} is nearly all rock", generator, seed);
+
+            let reached = cave.distances_from(first_floor(&cave)).iter().filter(|d| d.is_some()).count();
+            assert_eq!(reached, cave.walkable_count(), "{:?} seed {} has unreachable pockets", generator, seed);
+
+            for x in 0..cave.width {
+                assert!(!cave.is_walkable(x, 0) && !cave.is_walkable(x, cave.height - 1));
+            }
+            for y in 0..cave.height {
+                assert!(!cave.is_walkable(0, y) && !cave.is_walkable(cave.width - 1, y));
+            }
+        }
+    }
+}
+
+#[test]
+fn player_and_doors_land_on_floor_far_apart() {
+    let mut level: LevelData = ron::from_str(r#"(
+        id: "deep_cave",
+        name: "Deep Cave",
+        room_type: "cave",
+        player_start: (-2000.0, -2000.0),
+        cave: Some((seed: Some(7), generator: DrunkardsWalk)),
+        doors: [
+            (leads_to: "level_02", label: "Out", locked: false),
+            (leads_to: "level_02", label: "Also out", locked: false),
+        ],
+        items: [(item_type: "coin", position: (0.0, 0.0))],
+    )"#).unwrap();
+    let cave = place_in_cave(&mut level).expect("cave rooms get a layout");
+
+    let tile_of = |position: (f32, f32)| {
+        (0..cave.height)
+            .flat_map(|y| (0..cave.width).map(move |x| (x, y)))
+            .find(|(x, y)| cave.position(*x, *y) == position)
+            .expect("placed on a tile")
+    };
+    let start = tile_of(level.player_start);
+    // player_start is only a hint: the player goes to the nearest floor, which is near the corner
+    assert!(cave.is_walkable(start.0, start.1));
+    assert!(start.0 + start.1 < cave.width, "{:?}", start);
+
+    let distances = cave.distances_from(start);
+    let longest = distances.iter().flatten().max().copied().unwrap();
+    let doors: Vec<(usize, usize)> = level.doors.iter().map(|door| tile_of(door.position)).collect();
+    assert_ne!(doors[0], doors[1]);
+    for door in &doors {
+        let steps = distances[door.1 * cave.width + door.0].expect("door is reachable");
+        assert!(steps * 2 >= longest, "door {} steps away, farthest floor is {}", steps, longest);
+    }
+    let coin = tile_of(level.items[0].position);
+    assert!(cave.is_walkable(coin.0, coin.1));
+}
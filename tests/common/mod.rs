@@ -0,0 +1,263 @@
+//! Headless game for integration tests: no GPU, no real window, manual time and input.
+#![allow(dead_code)] // Each test file uses a different slice of the harness
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use bevy::asset::AssetPlugin;
+use bevy::audio::AudioSource;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{PrimaryWindow, WindowResolution};
+
+use bevy_game::GamePlugin;
+use bevy_game::level::CurrentLevel;
+use bevy_game::player::{Player, PlayerHealth};
+use bevy_game::run::{CurrentRun, DEFAULT_RUN};
+use bevy_game::run_schema::RunData;
+use bevy_game::save::SaveDir;
+use bevy_game::state::GameState;
+use bevy_game::story_flags::StoryFlags;
+
+pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+/// Upper bound for waits, so a broken flow fails instead of hanging
+pub const MAX_WAIT_FRAMES: u32 = 60 * 60;
+
+/// Parse a RON file under `assets/`, straight from disk
+pub fn read_asset<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    let text = std::fs::read_to_string(format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), path)).unwrap();
+    ron::from_str(&text).unwrap()
+}
+
+static NEXT_SAVE_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Empty directory under `$TMP/doorgame-tests`, deleted again when this is dropped.
+/// Tests in one binary run in parallel, so each needs its own name.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join("doorgame-tests")
+            .join(format!("{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub struct TestGame {
+    pub app: App,
+    window: Entity,
+    /// Each game gets its own save dir so autosaves don't touch real saves or other tests
+    save_dir: TempDir,
+}
+
+impl TestGame {
+    /// Game sitting in the Loading state, with a 1280x720 virtual window
+    pub fn new() -> Self {
+        Self::with_window_size(1280, 720)
+    }
+
+    pub fn with_window_size(width: u32, height: u32) -> Self {
+        let save_dir = TempDir::new(&format!("save-{}", NEXT_SAVE_DIR.fetch_add(1, Ordering::Relaxed)));
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: concat!(env!("CARGO_MANIFEST_DIR"), "/assets").to_string(),
+                ..default()
+            },
+            StatesPlugin,
+            InputPlugin,
+        ))
+        // Sound effects are requested by path; with no audio output they just never play
+        .init_asset::<AudioSource>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+        .insert_resource(SaveDir(save_dir.to_path_buf()))
+        .add_plugins(GamePlugin);
+
+        let window = app.world_mut().spawn((
+            Window {
+                resolution: WindowResolution::new(width, height),
+                ..default()
+            },
+            PrimaryWindow,
+        )).id();
+
+        let mut game = Self { app, window, save_dir };
+        game.step(1); // Startup + enter Loading
+        game
+    }
+
+    /// Skip the title menu and load a level straight from `assets/levels/<id>.ron`.
+    /// Returns once the level is spawned and the game has moved on from LoadingNewLevel.
+    pub fn load_level(&mut self, level_id: &str) -> &mut Self {
+        {
+            let mut current_level = self.app.world_mut().resource_mut::<CurrentLevel>();
+            current_level.level_id = level_id.to_string();
+            current_level.loaded = false;
+        }
+        self.set_state(GameState::LoadingNewLevel);
+        self.step(1);
+        let loaded = self.run_until(|game| game.state() != GameState::LoadingNewLevel);
+        assert!(loaded, "level '{}' never finished loading", level_id);
+        self
+    }
+
+    /// Start the default run with this seed, like the title menu does. Only `CurrentLevel`
+    /// moves to the first room; load it with `load_level(&game.level_id())`.
+    pub fn start_run(&mut self, seed: u64) -> &mut Self {
+        let data: RunData = read_asset(&format!("runs/{}.run.ron", DEFAULT_RUN));
+        self.app.world_mut().resource_scope(|world, mut current_run: Mut<CurrentRun>| {
+            bevy_game::run::start_run(&mut current_run, &mut world.resource_mut::<CurrentLevel>(), &data, seed);
+        });
+        self
+    }
+
+    pub fn step(&mut self, frames: u32) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    pub fn step_seconds(&mut self, seconds: f32) -> &mut Self {
+        self.step((seconds / FRAME.as_secs_f32()).ceil() as u32)
+    }
+
+    /// Step until `done` holds (checked before each frame). False if it never did.
+    pub fn run_until(&mut self, mut done: impl FnMut(&mut Self) -> bool) -> bool {
+        for _ in 0..MAX_WAIT_FRAMES {
+            if done(self) {
+                return true;
+            }
+            self.app.update();
+        }
+        done(self)
+    }
+
+    pub fn wait_for_state(&mut self, state: GameState) -> bool {
+        self.run_until(|game| game.state() == state)
+    }
+
+    pub fn press(&mut self, key: KeyCode) -> &mut Self {
+        self.send_key(key, ButtonState::Pressed)
+    }
+
+    pub fn release(&mut self, key: KeyCode) -> &mut Self {
+        self.send_key(key, ButtonState::Released)
+    }
+
+    /// Press for one frame, then release
+    pub fn tap(&mut self, key: KeyCode) -> &mut Self {
+        self.press(key).step(1).release(key).step(1)
+    }
+
+    /// Hold a key for a number of frames
+    pub fn hold(&mut self, key: KeyCode, frames: u32) -> &mut Self {
+        self.press(key).step(frames).release(key).step(1)
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) -> &mut Self {
+        let window = self.window;
+        self.app.world_mut().write_message(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(bevy::input::keyboard::NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window,
+        });
+        self
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world().resource::<State<GameState>>().get()
+    }
+
+    pub fn set_state(&mut self, state: GameState) {
+        self.app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        self.app.world_mut().resource_mut::<R>()
+    }
+
+    pub fn flags(&self) -> &StoryFlags {
+        self.resource::<StoryFlags>()
+    }
+
+    pub fn health(&self) -> &PlayerHealth {
+        self.resource::<PlayerHealth>()
+    }
+
+    pub fn level_id(&self) -> String {
+        self.resource::<CurrentLevel>().level_id.clone()
+    }
+
+    pub fn player_position(&mut self) -> Vec2 {
+        let mut query = self.app.world_mut().query_filtered::<&Transform, With<Player>>();
+        query.single(self.app.world()).expect("no player").translation.truncate()
+    }
+
+    pub fn teleport_player(&mut self, pos: Vec2) -> &mut Self {
+        let mut query = self.app.world_mut().query_filtered::<&mut Transform, With<Player>>();
+        let mut transform = query.single_mut(self.app.world_mut()).expect("no player");
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y;
+        self
+    }
+
+    /// Positions of every entity with component `C`
+    pub fn positions_of<C: Component>(&mut self) -> Vec<Vec2> {
+        let mut query = self.app.world_mut().query_filtered::<&Transform, With<C>>();
+        query.iter(self.app.world()).map(|t| t.translation.truncate()).collect()
+    }
+
+    pub fn count<C: Component>(&mut self) -> usize {
+        let mut query = self.app.world_mut().query_filtered::<(), With<C>>();
+        query.iter(self.app.world()).count()
+    }
+
+    /// Every UI text on screen, including the dialogue panel's
+    pub fn shown_texts(&mut self) -> Vec<String> {
+        let mut query = self.app.world_mut().query::<&Text>();
+        query.iter(self.app.world()).map(|text| text.0.clone()).collect()
+    }
+
+    /// Tap Space until the dialogue is over (or give up after `max_lines`)
+    pub fn skip_dialogue(&mut self, max_lines: u32) -> &mut Self {
+        for _ in 0..max_lines {
+            if self.state() != GameState::Dialogue {
+                break;
+            }
+            self.tap(KeyCode::Space);
+        }
+        // Let the state transition out of Dialogue apply
+        self.step(1)
+    }
+}
@@ -0,0 +1,69 @@
+// Needs the dev feature: cargo test --features dev --test dev_console
+#![cfg(feature = "dev")]
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::dev_console::DevConsole;
+use bevy_game::player::{GodMode, PlayerHealth};
+use bevy_game::state::GameState;
+use bevy_game::story_flags::FlagValue;
+use common::TestGame;
+
+fn run(game: &mut TestGame, line: &str) -> String {
+    game.resource_mut::<DevConsole>().submit(line);
+    game.step(1);
+    game.resource::<DevConsole>().log().last().cloned().unwrap_or_default()
+}
+
+#[test]
+fn sets_and_lists_flags() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening").skip_dialogue(10);
+
+    run(&mut game, "flag set duck_status Text(\"traded\")");
+    assert_eq!(game.flags().get("duck_status"), Some(&FlagValue::Text("traded".to_string())));
+
+    run(&mut game, "flag list");
+    assert!(game.resource::<DevConsole>().log().contains(&"duck_status = Text(\"traded\")".to_string()));
+
+    assert!(run(&mut game, "flag set coins three").starts_with("error: bad value"));
+}
+
+#[test]
+fn goto_loads_the_level() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening").skip_dialogue(10);
+
+    run(&mut game, "goto boss_test");
+    assert!(game.run_until(|game| game.state() != GameState::LoadingNewLevel && game.level_id() == "boss_test"));
+}
+
+#[test]
+fn heal_and_god() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening").skip_dialogue(10);
+    game.resource_mut::<PlayerHealth>().current = 1;
+
+    run(&mut game, "heal");
+    assert_eq!(game.health().current, game.health().max);
+
+    assert_eq!(run(&mut game, "god"), "god mode on");
+    assert!(game.resource::<GodMode>().0);
+}
+
+#[test]
+fn open_console_swallows_game_keys() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening").skip_dialogue(10);
+    let start = game.player_position();
+
+    game.tap(KeyCode::Backquote);
+    assert!(game.resource::<DevConsole>().open);
+    game.hold(KeyCode::KeyD, 30);
+    assert_eq!(game.player_position(), start);
+
+    game.tap(KeyCode::Escape);
+    assert!(!game.resource::<DevConsole>().open);
+    assert_eq!(game.state(), GameState::Playing);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::level::LoadedLevelData;
+use bevy_game::state::GameState;
+use bevy_game::story_flags::FlagValue;
+use common::TestGame;
+
+#[test]
+fn dialogue_advances_one_line_per_press() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening");
+    assert_eq!(game.state(), GameState::Dialogue);
+
+    // Six lines: five presses keep us in dialogue, the sixth ends it
+    for _ in 0..5 {
+        game.tap(KeyCode::Space);
+        assert_eq!(game.state(), GameState::Dialogue);
+    }
+    game.tap(KeyCode::Space).step(1);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn conditional_line_only_shows_when_flag_matches() {
+    // Duck alive: three plain lines
+    let mut game = TestGame::new();
+    game.load_level("level_02");
+    for _ in 0..3 {
+        game.tap(KeyCode::Space);
+    }
+    game.step(1);
+    assert_eq!(game.state(), GameState::Playing);
+
+    // Duck died in the boss fight: the fourth line is shown too
+    let mut game = TestGame::new();
+    game.resource_mut::<bevy_game::story_flags::StoryFlags>()
+        .set("duck_status", FlagValue::Text("died_in_boss".to_string()));
+    game.load_level("level_02");
+    for _ in 0..3 {
+        game.tap(KeyCode::Space);
+    }
+    game.step(1);
+    assert_eq!(game.state(), GameState::Dialogue);
+    game.tap(KeyCode::Space).step(1);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn choice_can_queue_more_dialogue() {
+    let mut game = TestGame::new();
+    game.load_level("level_02");
+    assert_eq!(game.state(), GameState::Dialogue);
+    {
+        let mut loaded = game.resource_mut::<LoadedLevelData>();
+        let level = loaded.0.as_mut().expect("level is loaded");
+        // The choice is on the last line, so picking it must not end the conversation
+        level.dialogue = ron::from_str(r#"[
+            (speaker: "Duck", text: "Want to hear a story?", choices: [
+                (text: "Yes", actions: [QueueDialogue(lines: [
+                    (speaker: "Duck", text: "Once upon a time"),
+                    (speaker: "Duck", text: "The end"),
+                ], then: "Playing")]),
+                (text: "No"),
+            ]),
+        ]"#).expect("dialogue parses");
+        level.reactions = ron::from_str(r#"[
+            (trigger: Event("DialogueComplete"), actions: [SetFlag(key: "story_over", value: Bool(true))]),
+        ]"#).expect("reactions parse");
+    }
+
+    game.tap(KeyCode::Digit1);
+    assert_eq!(game.state(), GameState::Dialogue);
+    assert!(game.shown_texts().contains(&"Once upon a time".to_string()));
+    assert_eq!(game.flags().get_bool("story_over"), None);
+
+    game.tap(KeyCode::Space);
+    assert!(game.shown_texts().contains(&"The end".to_string()));
+    game.tap(KeyCode::Space).step(1);
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.flags().get_bool("story_over"), Some(true));
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::door::DoorHintText;
+use bevy_game::level::LevelDoor;
+use bevy_game::level_schema::LevelData;
+use bevy_game::run::{CurrentRun, Run};
+use bevy_game::run_schema::RunData;
+use bevy_game::state::GameState;
+use bevy_game::story_flags::{FlagValue, StoryFlags};
+use common::{TestGame, read_asset};
+
+fn hint_texts(game: &mut TestGame) -> Vec<String> {
+    let mut query = game.app.world_mut().query_filtered::<&Text2d, With<DoorHintText>>();
+    query.iter(game.app.world()).map(|text| text.0.clone()).collect()
+}
+
+fn door_color(game: &mut TestGame, position: Vec2) -> Color {
+    let mut query = game.app.world_mut().query_filtered::<(&Transform, &TextColor), With<LevelDoor>>();
+    query.iter(game.app.world()).find(|(transform, _)| transform.translation.truncate() == position).unwrap().1.0
+}
+
+#[test]
+fn run_doors_take_their_pools_hints() {
+    let data: RunData = read_asset("runs/default.run.ron");
+    let run = Run::new(&data, 11);
+    let mut level: LevelData = read_asset("levels/run_start.ron");
+    run.prepare_room(0, &mut level);
+
+    for (door, room) in level.doors.iter().zip(&run.plan.rooms[1]) {
+        let pool = data.pools.iter().find(|pool| pool.label == room.label).unwrap();
+        assert_eq!(door.hints, pool.hints);
+        assert!(!door.hints.is_empty());
+    }
+}
+
+#[test]
+fn lantern_reveals_true_hints_near_a_door() {
+    let mut game = TestGame::new();
+    game.start_run(42);
+    let start = game.level_id();
+    game.load_level(&start).skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+
+    // Nothing shows from across the room
+    game.step(2);
+    assert!(hint_texts(&mut game).is_empty());
+
+    let room = game.resource::<CurrentRun>().0.as_ref().unwrap().plan.rooms[1][0].clone();
+    let door = game.positions_of::<LevelDoor>().into_iter().min_by(|a, b| a.x.total_cmp(&b.x)).unwrap();
+    game.teleport_player(door - Vec2::new(0.0, 150.0)).step(2);
+    assert_eq!(hint_texts(&mut game), vec!["Something stirs behind this door.".to_string()], "{} door", room.label);
+    assert_eq!(door_color(&mut game, door), Color::WHITE);
+
+    game.resource_mut::<StoryFlags>().set("lantern", FlagValue::Bool(true));
+    game.step(2);
+    let true_hint = &room.hints[0];
+    let expected = format!("{}\n{}", true_hint.icon.as_ref().unwrap(), true_hint.text.as_ref().unwrap());
+    assert_eq!(hint_texts(&mut game), vec![expected]);
+    let (r, g, b) = true_hint.tint.unwrap();
+    assert_eq!(door_color(&mut game, door), Color::srgb(r, g, b));
+
+    // Walking off hides it again
+    game.teleport_player(Vec2::new(0.0, -200.0)).step(2);
+    assert!(hint_texts(&mut game).is_empty());
+    assert_eq!(door_color(&mut game, door), Color::WHITE);
+
+    // A door despawned mid-room (DespawnTagged) takes its hint with it
+    game.teleport_player(door - Vec2::new(0.0, 150.0)).step(2);
+    assert_eq!(hint_texts(&mut game).len(), 1);
+    let mut doors = game.app.world_mut().query_filtered::<(Entity, &Transform), With<LevelDoor>>();
+    let entity = doors.iter(game.app.world()).find(|(_, transform)| transform.translation.truncate() == door).unwrap().0;
+    game.app.world_mut().despawn(entity);
+    game.step(2);
+    assert!(hint_texts(&mut game).is_empty());
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::door::DoorMessage;
+use bevy_game::inventory::{Inventory, GENERIC_KEY};
+use bevy_game::item::{CollectedItems, ItemRegistry, spawn_item_from_data};
+use bevy_game::level::LevelDoor;
+use bevy_game::level_schema::{ConditionalDestination, ItemData};
+use bevy_game::story_flags::{FlagValue, StoryFlags};
+use bevy_game::save::{SaveDir, SaveSlot, load_slot};
+use bevy_game::state::GameState;
+use common::TestGame;
+
+#[test]
+fn opening_room_door_leads_to_next_room() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening");
+    assert_eq!(game.state(), GameState::Dialogue);
+
+    game.skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+
+    game.teleport_player(Vec2::new(0.0, 0.0)).step(2);
+    assert_eq!(game.level_id(), "level_00_1");
+    assert!(game.run_until(|game| matches!(game.state(), GameState::Playing | GameState::Dialogue)));
+}
+
+/// level_01_intro, with its Left Door sending players who have `shortcut` to boss_test
+fn intro_with_a_conditional_door() -> TestGame {
+    let mut game = TestGame::new();
+    game.load_level("level_01_intro").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+    let mut doors = game.app.world_mut().query::<&mut LevelDoor>();
+    for mut door in doors.iter_mut(game.app.world_mut()) {
+        if door.label == "Left Door" {
+            door.leads_to_if = vec![ron::from_str::<ConditionalDestination>(
+                r#"(condition: Has("shortcut"), level: "boss_test")"#,
+            ).unwrap()];
+        }
+    }
+    game
+}
+
+#[test]
+fn conditional_door_goes_where_its_flag_says() {
+    let mut game = intro_with_a_conditional_door();
+    game.resource_mut::<StoryFlags>().set("shortcut", FlagValue::Bool(true));
+
+    game.teleport_player(Vec2::new(-200.0, 200.0)).step(3);
+    assert_eq!(game.level_id(), "boss_test");
+}
+
+#[test]
+fn conditional_door_falls_back_to_leads_to() {
+    let mut game = intro_with_a_conditional_door();
+
+    game.teleport_player(Vec2::new(-200.0, 200.0)).step(3);
+    assert_eq!(game.level_id(), "level_02");
+}
+
+#[test]
+fn locked_door_needs_a_key() {
+    let mut game = TestGame::new();
+    game.load_level("level_02").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+
+    // Boss Door is locked and we have no key
+    game.teleport_player(Vec2::new(0.0, 250.0)).step(3);
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.level_id(), "level_02");
+    assert_eq!(game.count::<DoorMessage>(), 1);
+
+    // Pick up the key in the room, then try again
+    game.teleport_player(Vec2::new(-150.0, -100.0)).step(3);
+    assert!(game.resource::<Inventory>().has_key(GENERIC_KEY));
+
+    game.teleport_player(Vec2::new(0.0, 250.0)).step(3);
+    assert_eq!(game.level_id(), "boss_test");
+    assert!(game.resource::<Inventory>().has_key(GENERIC_KEY), "Boss Door doesn't use the key up");
+}
+
+#[test]
+fn boss_door_opens_again_after_dying_to_the_boss() {
+    let mut game = TestGame::new();
+    game.load_level("level_02").skip_dialogue(10);
+    game.teleport_player(Vec2::new(-150.0, -100.0)).step(3);
+    game.teleport_player(Vec2::new(0.0, 250.0)).step(3);
+    assert_eq!(game.level_id(), "boss_test");
+
+    game.set_state(GameState::Defeat);
+    game.step(1).tap(KeyCode::Escape);
+    assert_eq!(game.level_id(), bevy_game::level::START_LEVEL);
+    assert!(!game.resource::<Inventory>().has_key(GENERIC_KEY), "nothing carries over from the last attempt");
+
+    // The key is back in its room
+    game.load_level("level_02").skip_dialogue(10);
+    game.teleport_player(Vec2::new(-150.0, -100.0)).step(3);
+    assert!(game.resource::<Inventory>().has_key(GENERIC_KEY));
+
+    game.teleport_player(Vec2::new(0.0, 250.0)).step(3);
+    assert_eq!(game.level_id(), "boss_test");
+}
+
+#[test]
+fn missing_level_shows_error_and_can_go_back() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening").skip_dialogue(10);
+
+    game.load_level("no_such_level");
+    assert_eq!(game.state(), GameState::LoadError);
+    let error = game.resource::<bevy_game::state::load_error::LevelLoadError>();
+    assert_eq!(error.path, "levels/no_such_level.ron");
+    let save = load_slot(&game.resource::<SaveDir>().0, game.resource::<SaveSlot>().0).unwrap();
+    assert_eq!(save.level_id, "level_00_opening", "a room that didn't load isn't the Continue point");
+
+    // First option is the room we came from
+    game.tap(KeyCode::Enter);
+    assert!(game.wait_for_state(GameState::Dialogue));
+    assert_eq!(game.level_id(), "level_00_opening");
+}
+
+#[test]
+fn coins_picked_up_again_after_dying_are_not_counted_twice() {
+    let coin: ItemData = ron::from_str(r#"(item_type: "coin", position: (150.0, 100.0))"#).unwrap();
+    let mut game = TestGame::new();
+    for _ in 0..2 {
+        game.load_level("level_02").skip_dialogue(10);
+        // As if level_02 had a coin: same id every time, so it only comes back if it isn't collected
+        if !game.resource::<CollectedItems>().0.contains("level_02/coin") {
+            game.app.world_mut().resource_scope(|world, registry: Mut<ItemRegistry>| {
+                spawn_item_from_data(&mut world.commands(), &coin, Some("level_02/coin".to_string()), &registry);
+                world.flush();
+            });
+        }
+        game.step(1).teleport_player(Vec2::new(150.0, 100.0)).step(3);
+        assert_eq!(game.flags().get_number("coins"), Some(1));
+
+        game.set_state(GameState::Defeat);
+        game.step(1).tap(KeyCode::Escape);
+    }
+    assert_eq!(game.flags().get_number("coins"), None, "coins go with the items that gave them");
+}
@@ -0,0 +1,95 @@
+// Needs the dev feature: cargo test --features dev --test hot_reload
+#![cfg(feature = "dev")]
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::level::{CurrentLevel, LevelDoor};
+use bevy_game::level_schema::LevelData;
+use bevy_game::state::GameState;
+use bevy_game::state::boss_fight::{AttackTimer, Boss, PlayerArena};
+use bevy_game::story_flags::{FlagValue, StoryFlags};
+use common::TestGame;
+
+// Editing the asset emits the same AssetEvent::Modified the file watcher does
+fn edit_current_level(game: &mut TestGame, edit: impl FnOnce(&mut LevelData)) {
+    let handle = game.resource::<CurrentLevel>().handle.clone();
+    let mut levels = game.resource_mut::<Assets<LevelData>>();
+    edit(levels.get_mut(&handle).expect("current level is loaded"));
+}
+
+// Partway through the fight: phase 2, some volleys already fired
+fn enter_phase_two(game: &mut TestGame) {
+    game.resource_mut::<StoryFlags>().set("boss_phase", FlagValue::Number(2));
+    let mut attack_timer = game.resource_mut::<AttackTimer>();
+    attack_timer.phase = 2;
+    attack_timer.projectiles_fired = 4;
+    attack_timer.max_projectiles = 40;
+}
+
+fn assert_fight_kept(game: &TestGame) {
+    assert_eq!(game.flags().get_number("boss_phase"), Some(2));
+    let attack_timer = game.resource::<AttackTimer>();
+    assert_eq!(attack_timer.phase, 2);
+    assert_eq!(attack_timer.max_projectiles, 40);
+    assert!(attack_timer.projectiles_fired >= 4);
+}
+
+#[test]
+fn edited_level_respawns_in_place() {
+    let mut game = TestGame::new();
+    game.load_level("level_02").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+    let doors = game.count::<LevelDoor>();
+
+    game.teleport_player(Vec2::new(120.0, -40.0)).step(1);
+    edit_current_level(&mut game, |level| {
+        let mut door = level.doors[0].clone();
+        door.position = (200.0, 200.0);
+        door.label = "New Door".to_string();
+        level.doors.push(door);
+    });
+    game.step(2);
+
+    assert_eq!(game.count::<LevelDoor>(), doors + 1);
+    assert_eq!(game.player_position(), Vec2::new(120.0, -40.0));
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn boss_room_gets_its_arena_back() {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+    enter_phase_two(&mut game);
+
+    edit_current_level(&mut game, |level| level.name.push_str(" (edited)"));
+    game.step(2);
+
+    assert_eq!(game.state(), GameState::BossFight);
+    assert_eq!(game.count::<Boss>(), 1);
+    assert_eq!(game.count::<PlayerArena>(), 1);
+    assert_fight_kept(&game);
+}
+
+#[test]
+fn boss_room_reloaded_in_dialogue_gets_its_arena_back() {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+
+    enter_phase_two(&mut game);
+
+    // A mid-fight line, like a queued dialogue
+    game.set_state(GameState::Dialogue);
+    game.step(1);
+    edit_current_level(&mut game, |level| level.name.push_str(" (edited)"));
+    game.step(2);
+    assert_eq!(game.count::<Boss>(), 1);
+
+    game.skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+    assert_eq!(game.count::<Boss>(), 1);
+    assert_eq!(game.count::<PlayerArena>(), 1);
+    assert_fight_kept(&game);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::fs;
+use bevy_game::level_graph::{GraphFormat, render_graph};
+use bevy_game::level_index::read_levels;
+use common::TempDir;
+
+// Each test gets its own directory; tests in one binary run in parallel
+fn fixture_levels(name: &str) -> Vec<bevy_game::level_index::LevelFile> {
+    let dir = TempDir::new(&format!("graph-{}", name));
+    fs::write(dir.join("level_00_opening.ron"), r#"(
+        id: "level_00_opening",
+        name: "Start",
+        room_type: "square",
+        player_start: (0.0, 0.0),
+        doors: [
+            (
+                position: (0.0, 0.0),
+                leads_to: "hall",
+                leads_to_if: [(condition: Equals("duck_status", Text("alive")), level: "pond")],
+                label: "Fork",
+                locked: true,
+                key_required: Some("red_key"),
+            ),
+        ],
+    )"#).unwrap();
+    fs::write(dir.join("hall.ron"), r#"(
+        id: "hall",
+        name: "Hall",
+        room_type: "boss",
+        player_start: (0.0, 0.0),
+        doors: [],
+        reactions: [(trigger: Event("ProjectilesDone"), actions: [TransitionToLevel(level_id: "level_00_opening")])],
+    )"#).unwrap();
+    read_levels(&dir).unwrap()
+}
+
+#[test]
+fn dot_graph_shows_locks_conditions_and_missing_rooms() {
+    let dot = render_graph(&fixture_levels("dot"), GraphFormat::Dot);
+
+    assert!(dot.starts_with("digraph levels {"));
+    assert!(dot.contains(r#""level_00_opening" -> "hall" [label="Fork\nlocked (red_key)", color=goldenrod];"#), "{}", dot);
+    assert!(dot.contains(r#""level_00_opening" -> "pond" [label="Fork\nlocked (red_key)\nif duck_status = alive", style=dashed"#), "{}", dot);
+    assert!(dot.contains(r#""hall" -> "level_00_opening" [label="TransitionToLevel", style=dotted];"#), "{}", dot);
+    assert!(dot.contains(r#""hall" [label="Hall\nhall", shape=doubleoctagon"#), "{}", dot);
+    assert!(dot.contains(r#""pond" [label="pond\n(missing)""#), "{}", dot);
+}
+
+#[test]
+fn mermaid_graph_uses_flowchart_syntax() {
+    let mermaid = render_graph(&fixture_levels("mermaid"), GraphFormat::Mermaid);
+
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains(r#"hall{{"Hall<br/>hall"}}"#), "{}", mermaid);
+    assert!(mermaid.contains(r#"level__00__opening -.->|"Fork<br/>locked (red_key)<br/>if duck_status = alive"| pond"#), "{}", mermaid);
+    assert!(mermaid.contains("class pond problem"), "{}", mermaid);
+}
+
+#[test]
+fn mermaid_ids_never_collide() {
+    let dir = TempDir::new("graph-collide");
+    fs::write(dir.join("level_00_opening.ron"), r#"(
+        id: "level_00_opening",
+        name: "Start",
+        room_type: "square",
+        player_start: (0.0, 0.0),
+        doors: [
+            (position: (0.0, 0.0), leads_to: "a-b", label: "Dash", locked: false),
+            (position: (0.0, 0.0), leads_to: "a_b", label: "Underscore", locked: false),
+            (position: (0.0, 0.0), leads_to: "run_trap@3", label: "Run", locked: false),
+        ],
+    )"#).unwrap();
+    let mermaid = render_graph(&read_levels(&dir).unwrap(), GraphFormat::Mermaid);
+
+    assert!(mermaid.contains(r#"-->|"Dash"| a_2d_b"#), "{}", mermaid);
+    assert!(mermaid.contains(r#"-->|"Underscore"| a__b"#), "{}", mermaid);
+    assert!(mermaid.contains(r#"-->|"Run"| run__trap_40_3"#), "{}", mermaid);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use bevy_game::level_lint::{LintIssue, Severity, lint_assets};
+use common::TempDir;
+
+fn errors(issues: &[LintIssue]) -> Vec<String> {
+    issues.iter().filter(|i| i.severity == Severity::Error).map(|i| i.to_string()).collect()
+}
+
+fn fixture_dir(name: &str) -> TempDir {
+    let dir = TempDir::new(&format!("lint-{}", name));
+    fs::create_dir_all(dir.join("levels")).unwrap();
+    dir
+}
+
+fn write_level(dir: &Path, id: &str, body: &str) {
+    fs::write(dir.join("levels").join(format!("{}.ron", id)), body).unwrap();
+}
+
+#[test]
+fn shipped_levels_have_no_errors() {
+    let issues = lint_assets(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"))).unwrap();
+    assert!(errors(&issues).is_empty(), "{:#?}", errors(&issues));
+}
+
+#[test]
+fn reports_broken_references() {
+    let dir = fixture_dir("broken");
+    write_level(&dir, "level_00_opening", r#"(
+        id: "level_00_opening",
+        name: "Start",
+        room_type: "sqaure",
+        player_start: (0.0, 0.0),
+        dialogue: [
+            (speaker: "---", text: "Hi", condition: Has("met_ghost"), goto: Some("nowhere")),
+        ],
+        doors: [
+            (position: (0.0, 0.0), leads_to: "level_99", label: "Typo Door", locked: false, hints: [
+                (),
+                (condition: Has("torch"), icon: Some("!")),
+            ]),
+        ],
+        music: Some("no_such_track"),
+        reactions: [
+            (
+                trigger: Event("ProjectilesDnoe"),
+                actions: [QueueDialogue(lines: [], then: "Fighting")],
+            ),
+            (
+                trigger: All([Not(Event("BossDefaeted")), Flag(AtLeast("ghost_mood", 2))]),
+                actions: [],
+            ),
+            (trigger: HealthBelow(2), actions: [After(seconds: -1.0, actions: [TransitionToLevel(level_id: "level_98")])]),
+            (trigger: EventWith(event: "ZoneEntered", value: "midlne"), actions: [DespawnTagged(tag: "gohst")]),
+            (trigger: Event("BossDefeated"), actions: [Wait(seconds: inf), Repeat(times: 2, interval: 0.0, actions: [])]),
+        ],
+        zones: [
+            (name: "midline", position: (0.0, 0.0), size: (800.0, 0.0)),
+        ],
+    )"#);
+    write_level(&dir, "orphan", r#"(
+        id: "level_00_opening",
+        name: "Nobody comes here",
+        room_type: "square",
+        player_start: (0.0, 0.0),
+        boss: Some("shaky"),
+        doors: [],
+    )"#);
+    write_level(&dir, "crypt", r#"(
+        id: "crypt",
+        name: "Crypt",
+        room_type: "map",
+        map: Some("
+            ###D###
+            #..x..#
+            #######
+        "),
+        cave: Some((seed: Some(3))),
+        boss: Some("shaky"),
+        doors: [],
+        on_enter: [
+            Curse(name: "Frozen", rooms: 2, effect: Speed(0.0)),
+            Buff(name: "Fleeting", rooms: 0, effect: HealPerRoom(1)),
+        ],
+    )"#);
+    write_level(&dir, "garbage", "(id: ");
+    fs::create_dir_all(dir.join("bosses")).unwrap();
+    fs::write(dir.join("bosses").join("shaky.boss.ron"), r#"(
+        name: "Shaky",
+        art: "?",
+        arena: (size: (-300.0, 200.0), position: (0.0, 0.0)),
+        phases: [(interval: -0.5, patterns: [(pattern: Rain, wait: Some(NaN))])],
+    )"#).unwrap();
+    fs::create_dir_all(dir.join("runs")).unwrap();
+    fs::write(dir.join("runs").join("broken.run.ron"), r#"(
+        name: "Broken",
+        start: "crypt",
+        length: 3,
+        doors: 2,
+        pools: [(label: "Deep", weight: 1.0, min_depth: 2, levels: ["run_nowhere"])],
+        boss: [],
+    )"#).unwrap();
+
+    let issues = lint_assets(&dir).unwrap();
+    let all: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+    let has = |needle: &str| all.iter().any(|issue| issue.contains(needle));
+
+    assert!(has("unknown room_type 'sqaure'"), "{:#?}", all);
+    assert!(has("door 'Typo Door' leads to 'level_99'"), "{:#?}", all);
+    assert!(has("music 'no_such_track' not found"), "{:#?}", all);
+    assert!(has("unknown event 'ProjectilesDnoe'"), "{:#?}", all);
+    assert!(has("unknown event 'BossDefaeted'"), "{:#?}", all);
+    assert!(has("flag 'ghost_mood' is read but nothing ever sets it"), "{:#?}", all);
+    assert!(has("reaction 3 has no Event in its trigger"), "{:#?}", all);
+    assert!(has("After in reaction 3 waits -1 seconds"), "{:#?}", all);
+    assert!(has("Wait in reaction 5 waits inf seconds"), "{:#?}", all);
+    assert!(has("Repeat in reaction 5 has interval 0, so every run happens in the same frame"), "{:#?}", all);
+    assert!(has("TransitionToLevel in reaction 3 leads to 'level_98'"), "{:#?}", all);
+    assert!(has("reaction 4 listens for zone 'midlne'"), "{:#?}", all);
+    assert!(has("reaction 4 looks for tag 'gohst'"), "{:#?}", all);
+    assert!(has("zone 'midline' has size (800.0, 0.0)"), "{:#?}", all);
+    assert!(has("unknown state 'Fighting'"), "{:#?}", all);
+    assert!(has("goto 'nowhere'"), "{:#?}", all);
+    assert!(has("flag 'met_ghost' is read but nothing ever sets it"), "{:#?}", all);
+    assert!(has("levels/orphan.ron: id 'level_00_opening' doesn't match the file name"), "{:#?}", all);
+    assert!(has("id 'level_00_opening' is also used by"), "{:#?}", all);
+    assert!(has("levels/orphan.ron: can't be reached from level_00_opening"), "{:#?}", all);
+    assert!(has("levels/garbage.ron: does not parse"), "{:#?}", all);
+    assert!(has("levels/crypt.ron: map has 1 door tile(s) but the level has 0 door(s)"), "{:#?}", all);
+    assert!(has("levels/crypt.ron: map has no @"), "{:#?}", all);
+    assert!(has("levels/crypt.ron: has cave settings, but room_type 'map' doesn't use them"), "{:#?}", all);
+    assert!(has("levels/crypt.ron: map has unknown tile(s) 'x'"), "{:#?}", all);
+    assert!(has("door 'Typo Door' hint 1 has no icon, text or tint"), "{:#?}", all);
+    assert!(has("door 'Typo Door' hint 1 always matches"), "{:#?}", all);
+    assert!(has("flag 'torch' is read but nothing ever sets it"), "{:#?}", all);
+    assert!(has("levels/crypt.ron: on_enter 'Frozen' has Speed(0)"), "{:#?}", all);
+    assert!(has("levels/crypt.ron: on_enter 'Fleeting' lasts 0 rooms"), "{:#?}", all);
+    let interval_issues = all.iter().filter(|issue| issue.contains("bosses/shaky.boss.ron: phase 1 has interval -0.5")).count();
+    assert_eq!(interval_issues, 1, "a boss used by two levels is checked once: {:#?}", all);
+    assert!(has("bosses/shaky.boss.ron: phase 1 pattern 1 has wait NaN"), "{:#?}", all);
+    assert!(has("bosses/shaky.boss.ron: arena has size (-300.0, 200.0)"), "{:#?}", all);
+    assert!(has("runs/broken.run.ron: pool 'Deep' uses 'run_nowhere', which has no level file"), "{:#?}", all);
+    assert!(has("runs/broken.run.ron: no pool can come up at depth 1"), "{:#?}", all);
+    assert!(has("runs/broken.run.ron: has no boss"), "{:#?}", all);
+    assert!(!has("levels/crypt.ron: can't be reached"), "run levels count as reachable: {:#?}", all);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_game::boss_schema::PatternStep;
+use bevy_game::bullet_pattern::{PatternTarget, fire_pattern};
+use bevy_game::level::LoadedLevelData;
+use bevy_game::player::GodMode;
+use bevy_game::projectile::{Homing, Projectile, ProjectilePool, Wave};
+use bevy_game::state::GameState;
+use bevy_game::state::boss_fight::AttackTimer;
+use common::TestGame;
+
+/// Boss room where the boss has stopped firing and its shots are all gone
+fn quiet_boss_room() -> TestGame {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+    game.resource_mut::<GodMode>().0 = true;
+    game.resource_mut::<LoadedLevelData>().0.as_mut().expect("level is loaded").reactions.clear();
+    {
+        let mut attack_timer = game.resource_mut::<AttackTimer>();
+        attack_timer.projectiles_fired = attack_timer.max_projectiles;
+        attack_timer.event_sent = true;
+        attack_timer.boss_defeated = true;
+    }
+    assert!(game.run_until(|game| game.count::<Projectile>() == 0), "boss shots never went away");
+    game
+}
+
+fn fire(game: &mut TestGame, steps: &str) {
+    let steps: Vec<PatternStep> = ron::from_str(steps).expect("steps parse");
+    let target = PatternTarget {
+        boss: Vec2::new(0.0, 200.0),
+        player: game.player_position(),
+        arena_center: Vec2::new(0.0, -100.0),
+        arena_size: Vec2::new(300.0, 200.0),
+    };
+    game.app.world_mut().resource_scope(|world, mut pool: Mut<ProjectilePool>| {
+        let mut spiral_angle = 0.0;
+        for step in &steps {
+            fire_pattern(&mut world.commands(), &mut pool, step, &target, &mut spiral_angle);
+        }
+        world.flush();
+    });
+    game.step(1);
+}
+
+fn projectile_entities(game: &mut TestGame) -> HashSet<Entity> {
+    let mut query = game.app.world_mut().query_filtered::<Entity, With<Projectile>>();
+    query.iter(game.app.world()).collect()
+}
+
+#[test]
+fn retired_projectiles_are_reused_without_their_old_behaviour() {
+    let mut game = quiet_boss_room();
+
+    // Homing and weaving shots that expire, and fast ones that leave the screen
+    fire(&mut game, r#"[
+        (pattern: Homing(turn_rate: 90.0), count: 4, lifetime: Some(0.2)),
+        (pattern: Sine(amplitude: 20.0, frequency: 2.0), count: 4, lifetime: Some(0.2)),
+        (pattern: Radial, count: 4, speed: 20000.0),
+    ]"#);
+    assert_eq!(game.count::<Projectile>(), 12);
+    assert!(game.count::<Homing>() > 0 && game.count::<Wave>() > 0);
+    assert!(game.run_until(|game| game.count::<Projectile>() == 0), "shots were never retired");
+    game.step(1);
+
+    let mut all_entities = game.app.world_mut().query::<Entity>();
+    let existing: HashSet<Entity> = all_entities.iter(game.app.world()).collect();
+    fire(&mut game, r#"[(pattern: Radial, count: 12, speed: 0.0)]"#);
+    let second = projectile_entities(&mut game);
+    assert_eq!(second.len(), 12);
+    assert!(second.is_subset(&existing), "the second volley should reuse pooled entities");
+    assert_eq!(game.count::<Homing>(), 0, "reused shots must not keep steering");
+    assert_eq!(game.count::<Wave>(), 0, "reused shots must not keep weaving");
+}
@@ -0,0 +1,333 @@
+mod common;
+
+use bevy::ecs::message::Messages;
+use bevy::prelude::*;
+
+use bevy_game::inventory::Inventory;
+use bevy_game::item::{CollectedItems, Item};
+use bevy_game::level_entity::Tagged;
+use bevy_game::npc::Npc;
+use bevy_game::wall::Wall;
+use bevy_game::level::LoadedLevelData;
+use bevy_game::level_event::LevelEvent;
+use bevy_game::level_schema::{Reaction, Trigger};
+use bevy_game::player::{GodMode, PlayerHealth};
+use bevy_game::reaction::{trigger_matches, FiredReactions, ScheduledActions, TriggerContext};
+use bevy_game::state::GameState;
+use bevy_game::state::boss_fight::PlayerArena;
+use bevy_game::story_flags::{FlagValue, StoryFlags};
+use common::TestGame;
+
+fn trigger(ron: &str) -> Trigger {
+    ron::from_str(ron).expect("trigger parses")
+}
+
+#[test]
+fn compound_triggers() {
+    let mut flags = StoryFlags::default();
+    flags.set("boss_phase", FlagValue::Number(2));
+    let mut inventory = Inventory::default();
+    inventory.add_key("vault_key");
+    let health = PlayerHealth { current: 1, max: 3 };
+    let npcs = vec!["duck".to_string()];
+    let context = TriggerContext {
+        event: &LevelEvent::ProjectilesDone,
+        story_flags: &flags,
+        health: &health,
+        inventory: &inventory,
+        npcs: &npcs,
+    };
+    let matches = |ron: &str| trigger_matches(&trigger(ron), &context);
+
+    assert!(matches(r#"All([Event("ProjectilesDone"), Flag(AtLeast("boss_phase", 2))])"#));
+    assert!(!matches(r#"All([Event("ProjectilesDone"), Flag(AtLeast("boss_phase", 3))])"#));
+    assert!(!matches(r#"All([Event("BossDefeated"), Flag(AtLeast("boss_phase", 2))])"#));
+    assert!(matches(r#"Any([HealthBelow(1), HealthBelow(2)])"#));
+    assert!(matches(r#"Not(HealthAtLeast(2))"#));
+    assert!(matches(r#"All([HasKey("vault_key"), Not(HasKey("key")), NpcPresent("Duck")])"#));
+    assert!(matches(r#"Flag(Not(LessThan("boss_phase", 2)))"#));
+}
+
+fn run_reactions(game: &mut TestGame, reactions: &str, events: u32) {
+    let reactions: Vec<Reaction> = ron::from_str(reactions).expect("reactions parse");
+    game.resource_mut::<LoadedLevelData>().0.as_mut().expect("level is loaded").reactions = reactions;
+    for _ in 0..events {
+        game.app.world_mut().write_message(LevelEvent::BossDefeated);
+        game.step(1);
+    }
+}
+
+/// Events sent in the last couple of frames
+fn recent_events(game: &TestGame) -> Vec<String> {
+    let messages = game.app.world().resource::<Messages<LevelEvent>>();
+    messages.get_cursor().read(messages).map(|event| event.name().to_string()).collect()
+}
+
+fn boss_room() -> TestGame {
+    let mut game = TestGame::new();
+    game.load_level("boss_test").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::BossFight);
+    game
+}
+
+#[test]
+fn highest_priority_match_wins() {
+    let mut game = boss_room();
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [SetFlag(key: "low", value: Bool(true))]),
+        (trigger: Event("BossDefeated"), priority: 5, actions: [SetFlag(key: "order", value: Text("high"))]),
+    ]"#, 1);
+    assert_eq!(game.flags().get_text("order"), Some("high"));
+    assert_eq!(game.flags().get_bool("low"), None);
+}
+
+#[test]
+fn fallthrough_lets_lower_matches_run_after() {
+    let mut game = boss_room();
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [SetFlag(key: "order", value: Text("low"))]),
+        (trigger: Event("BossDefeated"), priority: 5, fallthrough: true, actions: [SetFlag(key: "order", value: Text("high"))]),
+    ]"#, 1);
+
+    // The low priority reaction ran last
+    assert_eq!(game.flags().get_text("order"), Some("low"));
+}
+
+#[test]
+fn triggers_see_the_state_from_before_the_event() {
+    let mut game = boss_room();
+    run_reactions(&mut game, r#"[
+        (trigger: All([Event("BossDefeated"), Flag(Not(Has("stage")))]), actions: [SetFlag(key: "stage", value: Number(2))]),
+        (trigger: All([Event("BossDefeated"), Flag(Equals("stage", Number(2)))]), actions: [SetFlag(key: "second", value: Bool(true))]),
+    ]"#, 1);
+    assert_eq!(game.flags().get_number("stage"), Some(2));
+    assert_eq!(game.flags().get_bool("second"), None);
+
+    game.app.world_mut().write_message(LevelEvent::BossDefeated);
+    game.step(1);
+    assert_eq!(game.flags().get_bool("second"), Some(true));
+}
+
+#[test]
+fn once_reactions_fire_once() {
+    let mut game = boss_room();
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), once: true, fallthrough: true, actions: [GiveKey(name: "token")]),
+        (trigger: Event("BossDefeated"), actions: [GiveKey(name: "coin")]),
+    ]"#, 3);
+
+    let inventory = game.resource::<Inventory>();
+    assert_eq!(inventory.key_count("token"), 1);
+    assert_eq!(inventory.key_count("coin"), 3);
+}
+
+#[test]
+fn restarting_after_defeat_rearms_once_reactions() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening");
+    game.resource_mut::<FiredReactions>().0.insert("level_00_opening/0".to_string());
+
+    game.set_state(GameState::Defeat);
+    game.step(1).tap(KeyCode::Escape);
+    assert!(game.resource::<FiredReactions>().0.is_empty());
+}
+
+#[test]
+fn engine_sends_room_and_dialogue_events() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening");
+    assert!(recent_events(&game).contains(&"LevelEntered".to_string()));
+
+    game.skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [QueueDialogue(lines: [(speaker: "---", text: "Hm.")], then: "Playing")]),
+        (trigger: Event("DialogueComplete"), actions: [SetFlag(key: "talked", value: Bool(true))]),
+    ]"#, 1);
+    game.step(1);
+    assert_eq!(game.state(), GameState::Dialogue);
+
+    game.skip_dialogue(5);
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.flags().get_bool("talked"), Some(true));
+}
+
+#[test]
+fn reactions_can_queue_dialogue_during_dialogue() {
+    let mut game = TestGame::new();
+    game.load_level("level_02");
+    assert_eq!(game.state(), GameState::Dialogue);
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [QueueDialogue(lines: [(speaker: "???", text: "Did you hear that?")], then: "Playing")]),
+        (trigger: Event("DialogueComplete"), once: true, actions: [QueueDialogue(lines: [(speaker: "Duck", text: "One more thing.")], then: "Playing")]),
+    ]"#, 1);
+
+    // An event in the middle of the room's dialogue takes over the panel
+    assert_eq!(game.state(), GameState::Dialogue);
+    assert!(game.shown_texts().contains(&"Did you hear that?".to_string()));
+
+    // Finishing it queues one more line, which still gets shown instead of the room's intro
+    game.tap(KeyCode::Space).step(1);
+    assert_eq!(game.state(), GameState::Dialogue);
+    assert!(game.shown_texts().contains(&"One more thing.".to_string()));
+
+    game.tap(KeyCode::Space).step(1);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn exploration_rooms_react_to_pickups_and_doors() {
+    let mut game = TestGame::new();
+    game.load_level("level_02").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+    run_reactions(&mut game, r#"[
+        (trigger: EventWith(event: "ItemPicked", value: "key"), actions: [SetFlag(key: "found_key", value: Bool(true))]),
+        (trigger: EventWith(event: "ItemPicked", value: "heart"), actions: [SetFlag(key: "found_heart", value: Bool(true))]),
+        (trigger: EventWith(event: "DoorOpened", value: "Boss Door"), actions: [SetFlag(key: "left_through", value: Text("boss"))]),
+    ]"#, 0);
+
+    game.teleport_player(Vec2::new(-150.0, -100.0)).step(2);
+    assert_eq!(game.flags().get_bool("found_key"), Some(true));
+    assert_eq!(game.flags().get_bool("found_heart"), None);
+
+    // Reactions to the door run in this room, before the next one loads
+    game.teleport_player(Vec2::new(0.0, 250.0)).step(2);
+    assert_eq!(game.flags().get_text("left_through"), Some("boss"));
+    assert_eq!(game.level_id(), "boss_test");
+}
+
+#[test]
+fn wait_and_repeat_spread_actions_over_time() {
+    let mut game = boss_room();
+    game.resource_mut::<GodMode>().0 = true;
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), fallthrough: true, actions: [
+            SetFlag(key: "step", value: Number(1)),
+            Wait(seconds: 0.5),
+            SetFlag(key: "step", value: Number(2)),
+        ]),
+        (trigger: Event("BossDefeated"), fallthrough: true, actions: [Repeat(times: 3, interval: 0.25, actions: [GiveKey(name: "tick")])]),
+        (trigger: Event("BossDefeated"), actions: [After(seconds: 0.1, actions: [GiveKey(name: "after")]), GiveKey(name: "now")]),
+    ]"#, 1);
+
+    let keys = |game: &TestGame, name: &str| game.resource::<Inventory>().key_count(name);
+    assert_eq!(game.flags().get_number("step"), Some(1));
+    assert_eq!((keys(&game, "tick"), keys(&game, "now"), keys(&game, "after")), (1, 1, 0));
+
+    game.step_seconds(0.3);
+    assert_eq!(game.flags().get_number("step"), Some(1));
+    assert_eq!((keys(&game, "tick"), keys(&game, "after")), (2, 1));
+
+    game.step_seconds(0.5);
+    assert_eq!(game.flags().get_number("step"), Some(2));
+    assert_eq!(keys(&game, "tick"), 3);
+    assert!(game.resource::<ScheduledActions>().is_empty());
+}
+
+#[test]
+fn out_of_range_waits_count_as_zero() {
+    let mut game = boss_room();
+    game.resource_mut::<GodMode>().0 = true;
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), fallthrough: true, actions: [
+            Wait(seconds: inf),
+            SetFlag(key: "step", value: Number(2)),
+        ]),
+        (trigger: Event("BossDefeated"), actions: [Repeat(times: 2, interval: 1e20, actions: [GiveKey(name: "tick")])]),
+    ]"#, 1);
+    game.step(2);
+
+    assert_eq!(game.flags().get_number("step"), Some(2));
+    assert_eq!(game.resource::<Inventory>().key_count("tick"), 2);
+    assert!(game.resource::<ScheduledActions>().is_empty());
+}
+
+#[test]
+fn sequences_carry_on_after_a_queued_dialogue() {
+    let mut game = boss_room();
+    game.resource_mut::<GodMode>().0 = true;
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [
+            QueueDialogue(lines: [(speaker: "BOSS", text: "Enough!")], then: "BossFight"),
+            Wait(seconds: 0.5),
+            DespawnArena,
+        ]),
+    ]"#, 1);
+    game.step(1);
+    assert_eq!(game.state(), GameState::Dialogue);
+
+    // The clock doesn't run while the dialogue is up
+    game.step_seconds(1.0);
+    assert_eq!(game.count::<PlayerArena>(), 1);
+
+    game.skip_dialogue(3);
+    assert_eq!(game.state(), GameState::BossFight);
+    assert_eq!(game.count::<PlayerArena>(), 1);
+
+    game.step_seconds(0.6);
+    assert_eq!(game.count::<PlayerArena>(), 0);
+}
+
+#[test]
+fn scheduled_actions_stay_in_their_room() {
+    let mut game = boss_room();
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [
+            Wait(seconds: 0.5),
+            SetFlag(key: "late", value: Bool(true)),
+        ]),
+    ]"#, 1);
+    assert_eq!(game.resource::<ScheduledActions>().len(), 1);
+
+    game.load_level("level_02");
+    assert!(game.resource::<ScheduledActions>().is_empty());
+    game.skip_dialogue(10).step_seconds(1.0);
+    assert_eq!(game.flags().get("late"), None);
+}
+
+#[test]
+fn spawn_modify_and_despawn_by_tag() {
+    let mut game = TestGame::new();
+    game.load_level("level_02").skip_dialogue(10);
+    let npcs = game.count::<Npc>();
+    let walls = game.count::<Wall>();
+
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [
+            SpawnEntity(entity: Npc((name: "ghost", position: (-200.0, 0.0))), tag: Some("ghost")),
+            SpawnEntity(entity: Item((item_type: "coin", position: (200.0, 0.0))), tag: Some("ghost")),
+            SpawnEntity(entity: Wall(from: (-64.0, 100.0), to: (64.0, 100.0)), tag: Some("barrier")),
+        ]),
+    ]"#, 1);
+    assert_eq!(game.count::<Npc>(), npcs + 1);
+    assert_eq!(game.count::<Wall>(), walls + 5);
+    assert_eq!(game.count::<Tagged>(), 7);
+
+    run_reactions(&mut game, r#"[
+        (trigger: Event("BossDefeated"), actions: [
+            ModifyTagged(tag: "ghost", move_to: Some((300.0, 150.0))),
+            DespawnTagged(tag: "barrier"),
+        ]),
+    ]"#, 1);
+    assert_eq!(game.count::<Wall>(), walls);
+    let mut moved = game.app.world_mut().query_filtered::<&Transform, (With<Tagged>, Or<(With<Npc>, With<Item>)>)>();
+    let positions: Vec<Vec2> = moved.iter(game.app.world()).map(|transform| transform.translation.truncate()).collect();
+    assert_eq!(positions, vec![Vec2::new(300.0, 150.0); 2]);
+
+    // Spawned items aren't level items, so picking one up isn't remembered
+    let collected = game.resource::<CollectedItems>().0.clone();
+    game.teleport_player(Vec2::new(300.0, 150.0)).step(2);
+    assert_eq!(game.flags().get_number("coins"), Some(1));
+    assert_eq!(game.resource::<CollectedItems>().0, collected);
+}
+
+#[test]
+fn despawn_arena_keeps_the_room_walls() {
+    let mut game = boss_room();
+    let walls = game.count::<Wall>();
+    run_reactions(&mut game, r#"[(trigger: Event("BossDefeated"), actions: [DespawnArena])]"#, 1);
+
+    assert_eq!(game.count::<PlayerArena>(), 0);
+    let left = game.count::<Wall>();
+    assert!(left > 0 && left < walls, "{} of {} walls left", left, walls);
+}
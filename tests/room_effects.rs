@@ -0,0 +1,72 @@
+mod common;
+
+use bevy::prelude::KeyCode;
+use bevy_game::player::{BASE_MAX_HEALTH, PlayerHealth};
+use bevy_game::room_effect::ActiveEffects;
+use bevy_game::state::GameState;
+use bevy_game::ui::ActiveEffectsText;
+use common::TestGame;
+
+fn rooms_left(game: &TestGame, name: &str) -> Option<u32> {
+    game.resource::<ActiveEffects>().0.iter().find(|active| active.name == name).map(|active| active.rooms_left)
+}
+
+#[test]
+fn curse_hurts_and_wears_off_after_its_rooms() {
+    let mut game = TestGame::new();
+    game.load_level("run_curse@2");
+    game.skip_dialogue(5);
+
+    let health = game.health();
+    assert_eq!(health.current, health.max - 1);
+    assert_eq!(rooms_left(&game, "Heavy Legs"), Some(3));
+    assert!(game.resource::<ActiveEffects>().0[0].curse);
+    assert!((game.resource::<ActiveEffects>().speed_multiplier() - 0.6).abs() < 1e-6);
+    assert_eq!(game.count::<ActiveEffectsText>(), 1);
+
+    game.load_level("level_00_opening");
+    assert_eq!(rooms_left(&game, "Heavy Legs"), Some(2));
+    game.load_level("level_00_opening");
+    assert_eq!(rooms_left(&game, "Heavy Legs"), Some(1));
+    game.load_level("level_00_opening");
+    assert_eq!(rooms_left(&game, "Heavy Legs"), None);
+    assert_eq!(game.resource::<ActiveEffects>().speed_multiplier(), 1.0);
+    assert_eq!(game.count::<ActiveEffectsText>(), 1, "HUD is rebuilt for every room");
+}
+
+#[test]
+fn buff_heals_and_stacks_with_a_curse() {
+    let mut game = TestGame::new();
+    game.resource_mut::<PlayerHealth>().current = 1;
+    game.load_level("run_curse@2");
+    game.skip_dialogue(5);
+    assert_eq!(game.state(), GameState::Defeat, "the curse room's damage can finish the player");
+
+    let mut game = TestGame::new();
+    game.load_level("run_curse@2");
+    game.skip_dialogue(5);
+    let hurt = game.health().current;
+    game.load_level("run_buff@3");
+    assert_eq!(game.health().current, hurt + 1);
+    assert_eq!(rooms_left(&game, "Light Feet"), Some(3));
+    assert_eq!(rooms_left(&game, "Heavy Legs"), Some(2));
+    assert!((game.resource::<ActiveEffects>().speed_multiplier() - 1.3 * 0.6).abs() < 1e-6);
+
+    // Entering it again tops the buff back up instead of stacking it
+    game.load_level("run_buff@4");
+    assert_eq!(rooms_left(&game, "Light Feet"), Some(3));
+    assert_eq!(game.resource::<ActiveEffects>().0.len(), 2);
+}
+
+#[test]
+fn max_health_changes_end_with_defeat() {
+    let mut game = TestGame::new();
+    game.load_level("level_00_opening");
+    // As left by a MaxHealth(2) room
+    *game.resource_mut::<PlayerHealth>() = PlayerHealth { current: 0, max: BASE_MAX_HEALTH + 2 };
+
+    game.set_state(GameState::Defeat);
+    game.step(1).tap(KeyCode::Escape);
+    assert_eq!(game.health().max, BASE_MAX_HEALTH);
+    assert_eq!(game.health().current, BASE_MAX_HEALTH);
+}
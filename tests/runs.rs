@@ -0,0 +1,147 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::level::{LevelDoor, LoadedLevelData};
+use bevy_game::level_schema::LevelData;
+use bevy_game::inventory::Inventory;
+use bevy_game::item::CollectedItems;
+use bevy_game::player::{GodMode, PlayerModifiers};
+use bevy_game::run::{CurrentRun, DefaultRun, Run, RunPlan, run_room_id};
+use bevy_game::run_schema::RunData;
+use bevy_game::state::GameState;
+use bevy_game::story_flags::{FlagValue, StoryFlags};
+use common::{TestGame, read_asset};
+
+#[test]
+fn same_seed_same_run() {
+    let data: RunData = read_asset("runs/default.run.ron");
+    let plan = RunPlan::generate(&data, 42);
+    assert_eq!(plan, RunPlan::generate(&data, 42));
+    assert!((1..20).any(|seed| RunPlan::generate(&data, seed) != plan), "seed should change the run");
+
+    // Start, a row of doors per depth, then the boss
+    assert_eq!(plan.rooms.len() as u32, data.length + 2);
+    assert_eq!(plan.rooms[0][0].level, data.start);
+    assert!(data.boss.contains(&plan.rooms.last().unwrap()[0].level));
+    for (depth, row) in plan.rooms.iter().enumerate().skip(1).take(data.length as usize) {
+        assert_eq!(row.len() as u32, data.doors);
+        for room in row {
+            let pool = data.pools.iter().find(|pool| pool.label == room.label).unwrap();
+            assert!(pool.min_depth <= depth as u32, "{} came up at depth {}", room.label, depth);
+        }
+    }
+}
+
+#[test]
+fn run_rooms_take_their_doors_and_cave_from_the_run() {
+    let data: RunData = read_asset("runs/default.run.ron");
+    let run = Run::new(&data, 7);
+    let mut level: LevelData = read_asset("levels/run_buff.ron");
+    run.prepare_room(1, &mut level);
+
+    let expected: Vec<String> = run.plan.rooms[2].iter().map(|room| run_room_id(&room.level, 2)).collect();
+    let leads_to: Vec<String> = level.doors.iter().map(|door| door.leads_to.clone()).collect();
+    assert_eq!(leads_to, expected);
+    assert_eq!(level.cave.unwrap().seed, Some(run.cave_seed(1)));
+}
+
+#[test]
+fn doors_follow_the_plan_until_the_run_is_left() {
+    let mut game = TestGame::new();
+    game.start_run(42);
+    game.set_state(GameState::LoadingNewLevel);
+    game.step(1);
+    assert!(game.run_until(|game| game.state() != GameState::LoadingNewLevel));
+    game.skip_dialogue(10);
+    assert_eq!(game.level_id(), "run_start@0");
+
+    let plan = game.resource::<CurrentRun>().0.as_ref().unwrap().plan.clone();
+    let first = run_room_id(&plan.rooms[1][0].level, 1);
+    let mut doors = game.app.world_mut().query::<(&LevelDoor, &Transform)>();
+    let door = doors
+        .iter(game.app.world())
+        .find(|(door, _)| door.leads_to == first)
+        .map(|(_, transform)| transform.translation.truncate())
+        .expect("no door leads to the first room of the next row");
+    assert_eq!(doors.iter(game.app.world()).count(), plan.rooms[1].len());
+
+    game.teleport_player(door);
+    assert!(game.run_until(|game| game.state() == GameState::LoadingNewLevel));
+    assert_eq!(game.level_id(), first);
+    assert!(game.run_until(|game| game.state() != GameState::LoadingNewLevel));
+    assert_eq!(game.flags().get_number("run_depth"), Some(1));
+    let loaded = game.resource::<LoadedLevelData>().0.as_ref().unwrap();
+    assert!(loaded.doors.iter().all(|door| door.leads_to.ends_with("@2")));
+
+    // Anywhere outside the run's rooms ends it
+    game.load_level("level_00_opening");
+    assert!(game.resource::<CurrentRun>().0.is_none());
+}
+
+#[test]
+fn trap_turret_reloads_deeper_in_the_run() {
+    for (depth, volleys) in [(1, 1), (5, 2)] {
+        let mut game = TestGame::new();
+        game.resource_mut::<GodMode>().0 = true;
+        game.resource_mut::<StoryFlags>().set("run_depth", FlagValue::Number(depth));
+        game.load_level("run_trap@1");
+        assert_eq!(game.state(), GameState::BossFight);
+
+        assert!(game.wait_for_state(GameState::Dialogue), "turret never stopped at depth {}", depth);
+        assert_eq!(game.flags().get_number("boss_phase"), Some(volleys));
+        game.skip_dialogue(5);
+        assert_eq!(game.state(), GameState::Playing);
+    }
+}
+
+#[test]
+fn dying_in_a_run_starts_it_over() {
+    let mut game = TestGame::new();
+    game.start_run(42);
+    game.load_level("run_buff@3");
+
+    // What the attempt picked up along the way
+    game.resource_mut::<StoryFlags>().set("coins", FlagValue::Number(3));
+    game.resource_mut::<StoryFlags>().set("lantern", FlagValue::Bool(true));
+    game.resource_mut::<StoryFlags>().set("met_ghost", FlagValue::Bool(true));
+    game.resource_mut::<Inventory>().add_key("key");
+    game.resource_mut::<PlayerModifiers>().speed_multiplier = 2.0;
+    game.resource_mut::<CollectedItems>().0.extend(["run_buff@3/0".to_string(), "level_00_1/0".to_string()]);
+
+    game.set_state(GameState::Defeat);
+    game.step(1).tap(KeyCode::Escape);
+    assert!(game.run_until(|game| game.state() != GameState::LoadingNewLevel));
+
+    assert_eq!(game.level_id(), "run_start@0");
+    assert_eq!(game.resource::<CurrentRun>().0.as_ref().map(|run| run.seed), Some(42));
+    assert_eq!(game.flags().get_number("run_depth"), Some(0));
+    assert_eq!(game.flags().get("coins"), None);
+    assert_eq!(game.flags().get("lantern"), None);
+    assert_eq!(game.flags().get_bool("met_ghost"), Some(true), "only the run's own flags go");
+    assert_eq!(game.resource::<Inventory>().key_count("key"), 0);
+    assert_eq!(game.resource::<PlayerModifiers>().speed_multiplier, 1.0);
+    let collected = &game.resource::<CollectedItems>().0;
+    assert!(!collected.contains("run_buff@3/0"), "run rooms get their items back");
+    assert!(collected.contains("level_00_1/0"));
+}
+
+#[test]
+fn any_seed_can_be_typed_in() {
+    let mut game = TestGame::new();
+    assert!(game.wait_for_state(GameState::Title));
+    assert!(game.run_until(|game| {
+        let handle = game.resource::<DefaultRun>().0.clone();
+        game.resource::<Assets<RunData>>().contains(&handle)
+    }));
+
+    // u64::MAX, then one digit too many
+    for digit in "184467440737095516159".chars() {
+        let key = [
+            KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+            KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+        ][digit.to_digit(10).unwrap() as usize];
+        game.tap(key);
+    }
+    game.tap(KeyCode::KeyR);
+    assert_eq!(game.resource::<CurrentRun>().0.as_ref().map(|run| run.seed), Some(u64::MAX));
+}
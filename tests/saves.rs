@@ -0,0 +1,68 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::save::{SAVE_VERSION, SaveDir, load_slot, slot_path};
+use bevy_game::state::GameState;
+use common::TestGame;
+
+/// Put a save file in slot 1 before the title menu reads it
+fn write_slot_1(game: &TestGame, text: &str) {
+    let dir = &game.resource::<SaveDir>().0;
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(slot_path(dir, 1), text).unwrap();
+}
+
+#[test]
+fn title_screen_refuses_a_save_too_old_to_read() {
+    let mut game = TestGame::new();
+    write_slot_1(&game, r#"(version: 0, level_id: "level_02")"#);
+    assert!(game.wait_for_state(GameState::Title));
+    game.step(1);
+
+    let menu = game.shown_texts().join("\n");
+    assert!(menu.contains("Slot 1 - (save format v0 is no longer supported)"), "title menu was:\n{}", menu);
+}
+
+#[test]
+fn saves_without_fired_reactions_still_load() {
+    let game = TestGame::new();
+    write_slot_1(&game, r#"(
+        version: 1,
+        level_id: "level_02",
+        health: (current: 2, max: 3),
+        modifiers: (speed_multiplier: 1.0, shield: 0),
+        story_flags: (flags: {"duck_status": Text("alive")}),
+        inventory: (keys: {"key": 1}),
+        history: (visited: ["level_00_opening", "level_02"]),
+        collected_items: ["level_02/0"],
+    )"#);
+
+    let save = load_slot(&game.resource::<SaveDir>().0, 1).expect("save loads");
+    assert_eq!(save.version, SAVE_VERSION);
+    assert_eq!(save.level_id, "level_02");
+    assert!(save.inventory.has_key("key"));
+    assert!(save.fired_reactions.is_empty());
+    assert!(save.run.is_none());
+}
+
+#[test]
+fn enter_on_a_save_too_new_to_read_leaves_it_alone() {
+    let mut game = TestGame::new();
+    let too_new = r#"(version: 99, level_id: "level_02")"#;
+    write_slot_1(&game, too_new);
+    assert!(game.wait_for_state(GameState::Title));
+    game.step(1);
+
+    game.tap(KeyCode::Enter).step(5);
+    assert_eq!(game.state(), GameState::Title);
+    let menu = game.shown_texts().join("\n");
+    assert!(menu.contains("Slot 1 can't be continued"), "title menu was:\n{}", menu);
+    let path = slot_path(&game.resource::<SaveDir>().0, 1);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), too_new);
+
+    // Starting over keeps the old file next to the new save
+    game.tap(KeyCode::KeyN);
+    assert!(game.run_until(|game| matches!(game.state(), GameState::Playing | GameState::Dialogue)));
+    assert_eq!(std::fs::read_to_string(path.with_extension("ron.bak")).unwrap(), too_new);
+    assert!(load_slot(&game.resource::<SaveDir>().0, 1).is_ok(), "the new game autosaved over the empty slot");
+}
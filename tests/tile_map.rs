@@ -0,0 +1,83 @@
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_game::item::{CollectedItems, ItemRegistry};
+use bevy_game::level::{LevelDoor, TILE_SIZE, spawn_level_from_data_internal};
+use bevy_game::level_schema::LevelData;
+use bevy_game::npc::Npc;
+use bevy_game::story_flags::StoryFlags;
+use bevy_game::tile_map::{TileMap, place_on_map};
+use bevy_game::wall::Wall;
+use common::TestGame;
+
+// 7x5, indented like it would be inside a level file
+const CRYPT: &str = r#"(
+    id: "crypt",
+    name: "Crypt",
+    room_type: "map",
+    map: Some(r"
+        #######
+        #@..d.D
+        #.###.#
+        D..d..#
+        #######
+    "),
+    doors: [
+        (leads_to: "level_02", label: "East", locked: false),
+        (leads_to: "level_01_intro", label: "West", locked: false),
+    ],
+    npcs: [(name: "duck", position: (0.0, 0.0), extra: [Follow(speed: 5.0, distance: 50.0)])],
+)"#;
+
+fn crypt() -> LevelData {
+    ron::from_str(CRYPT).expect("crypt parses")
+}
+
+#[test]
+fn markers_place_player_doors_and_ducks() {
+    let map = TileMap::parse(crypt().map.as_deref().unwrap());
+    assert_eq!((map.width(), map.height()), (7, 5));
+    // Centred on the origin: the middle tile is at (0, 0)
+    assert_eq!(map.position(3, 2), (0.0, 0.0));
+    assert_eq!(map.position(0, 0), (-3.0 * TILE_SIZE, 2.0 * TILE_SIZE));
+
+    let mut level = crypt();
+    place_on_map(&mut level);
+    assert_eq!(level.player_start, map.position(1, 1));
+    // Doors take the D tiles in reading order
+    assert_eq!(level.doors[0].position, map.position(6, 1));
+    assert_eq!(level.doors[1].position, map.position(0, 3));
+    // The file's duck keeps its components; the spare d spawns a plain duck
+    assert_eq!(level.npcs.len(), 2);
+    assert_eq!(level.npcs[0].position, map.position(4, 1));
+    assert_eq!(level.npcs[0].extra.len(), 1);
+    assert_eq!(level.npcs[1].position, map.position(3, 3));
+
+    // Placing again (as a hot reload does) moves nothing
+    let before = (level.player_start, level.npcs.len());
+    level.player_start = (99.0, 99.0);
+    place_on_map(&mut level);
+    assert_eq!((level.player_start, level.npcs.len()), ((99.0, 99.0), before.1));
+}
+
+#[test]
+fn map_room_spawns_walls_doors_and_player() {
+    let mut game = TestGame::new();
+    let level = crypt();
+    game.app.world_mut().run_system_once(move |
+        mut commands: Commands,
+        windows: Query<&Window>,
+        story_flags: Res<StoryFlags>,
+        item_registry: Res<ItemRegistry>,
+        collected_items: Res<CollectedItems>,
+    | {
+        spawn_level_from_data_internal(&mut commands, &level, "crypt", &windows, &story_flags, &item_registry, &collected_items);
+    }).unwrap();
+
+    // 7 + 7 along the top and bottom, 5 across the middle, 1 on each door row
+    assert_eq!(game.count::<Wall>(), 21);
+    assert_eq!(game.count::<LevelDoor>(), 2);
+    assert_eq!(game.count::<Npc>(), 2);
+    assert_eq!(game.player_position(), Vec2::new(-2.0 * TILE_SIZE, TILE_SIZE));
+}
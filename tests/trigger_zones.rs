@@ -0,0 +1,64 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game::inventory::Inventory;
+use bevy_game::level::LoadedLevelData;
+use bevy_game::level_schema::{Reaction, TriggerZoneData};
+use bevy_game::state::GameState;
+use bevy_game::trigger_zone::{spawn_trigger_zone_from_data, TriggerZone};
+use common::TestGame;
+
+/// level_02 with a zone across the middle of the room, counting entries and exits as keys
+fn room_with_zone(once: bool) -> TestGame {
+    let mut game = TestGame::new();
+    game.load_level("level_02").skip_dialogue(10);
+    assert_eq!(game.state(), GameState::Playing);
+
+    let reactions: Vec<Reaction> = ron::from_str(r#"[
+        (trigger: EventWith(event: "ZoneEntered", value: "midline"), actions: [GiveKey(name: "entered")]),
+        (trigger: EventWith(event: "ZoneLeft", value: "midline"), actions: [GiveKey(name: "left")]),
+    ]"#).unwrap();
+    game.resource_mut::<LoadedLevelData>().0.as_mut().unwrap().reactions = reactions;
+
+    let zone = TriggerZoneData { name: "midline".to_string(), position: (0.0, 0.0), size: (200.0, 40.0), once };
+    let world = game.app.world_mut();
+    spawn_trigger_zone_from_data(&mut world.commands(), &zone);
+    world.flush();
+    game
+}
+
+fn counts(game: &TestGame) -> (u32, u32) {
+    let inventory = game.resource::<Inventory>();
+    (inventory.key_count("entered"), inventory.key_count("left"))
+}
+
+#[test]
+fn zone_sends_enter_and_leave() {
+    let mut game = room_with_zone(false);
+
+    game.step(3);
+    assert_eq!(counts(&game), (0, 0));
+
+    // Standing in the zone only counts as entering once
+    game.teleport_player(Vec2::new(0.0, 0.0)).step(5);
+    assert_eq!(counts(&game), (1, 0));
+
+    game.teleport_player(Vec2::new(0.0, -200.0)).step(2);
+    assert_eq!(counts(&game), (1, 1));
+
+    game.teleport_player(Vec2::new(50.0, 10.0)).step(2);
+    assert_eq!(counts(&game), (2, 1));
+}
+
+#[test]
+fn once_zone_is_gone_after_the_first_entry() {
+    let mut game = room_with_zone(true);
+
+    game.teleport_player(Vec2::new(0.0, 0.0)).step(2);
+    assert_eq!(counts(&game), (1, 0));
+    assert_eq!(game.count::<TriggerZone>(), 0);
+
+    game.teleport_player(Vec2::new(0.0, -200.0)).step(2);
+    game.teleport_player(Vec2::new(0.0, 0.0)).step(2);
+    assert_eq!(counts(&game), (1, 0));
+}